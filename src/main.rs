@@ -1,11 +1,14 @@
 use bevy::log::DEFAULT_FILTER;
 use bevy::window::WindowResolution;
-use bevy::{log::LogPlugin, prelude::*};
+use bevy::{ecs::schedule::ExecutorKind, log::LogPlugin, prelude::*};
 use vleue_kinetoscope::AnimatedImagePlugin;
 mod plugins;
 use plugins::{plants, toolbar, GridPos, PlayerTextureResources};
 
 pub const SCREEN_RESOLUTION: Vec2 = Vec2::new(800., 600.);
+/// How many times per second the gameplay is updated,
+/// whatever the frame rate is
+pub const TICKS_PER_SECOND: f64 = 60.;
 
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GameState {
@@ -15,9 +18,26 @@ pub enum GameState {
     End {
         win: bool,
     },
+    /// Passes straight through to Running, so that the level
+    /// is torn down and set up again
+    Restarting,
+}
+
+/// Whether the game is paused, only exists while running
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(GameState = GameState::Running)]
+pub enum PauseState {
+    #[default]
+    Playing,
+    Paused,
 }
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--headless") {
+        plugins::headless::run();
+        return;
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -36,26 +56,68 @@ fn main() {
                 }),
             AnimatedImagePlugin,
         ))
-        .insert_state(GameState::WaitForStart)
         .add_plugins((
-            plugins::land::LandPlugin,
-            plugins::zombies::ZombiePlugin,
-            plugins::plants::PlantPlugin,
-            toolbar::ToolbarPlugin,
+            GamePlugin,
+            plugins::progress_bar::ProgressBarPlugin,
             plugins::start_screen::StartScreen,
             plugins::end_screen::EndScreen,
+            plugins::pause_menu::PauseMenu,
+            plugins::save::SavePlugin,
+            plugins::settings::SettingsPlugin,
+            plugins::health_overlay::HealthOverlayPlugin,
+            plugins::death::DeathPlugin,
+            plugins::keyboard::KeyboardPlugin,
         ))
         .add_systems(Startup, setup)
-        .add_systems(PreStartup, PlayerTextureResources::setup)
         .add_systems(OnEnter(GameState::Running), debug_setup)
-        .add_systems(
-            PostUpdate,
-            remove_dying.run_if(in_state(GameState::Running)),
-        )
-        .add_observer(plugins::player::dead_cleaner)
         .run();
 }
 
+/// The game logic, shared by the window and the headless simulation.
+///
+/// Everything here works without a window or a renderer,
+/// screens and menus are added on top of it. Gameplay
+/// systems run in `FixedUpdate`, at [TICKS_PER_SECOND].
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_state(GameState::WaitForStart)
+            .add_sub_state::<PauseState>()
+            .enable_state_scoped_entities::<GameState>()
+            .enable_state_scoped_entities::<PauseState>()
+            .insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND))
+            // Gameplay systems run in the same order every tick,
+            // so that a game can be replayed exactly
+            .edit_schedule(FixedUpdate, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            })
+            .add_plugins((
+                plugins::interpolation::InterpolationPlugin,
+                plugins::level::LevelPlugin,
+                plugins::rng::RngPlugin,
+                plugins::land::LandPlugin,
+                plugins::lawnmower::LawnmowerPlugin,
+                plugins::zombies::ZombiePlugin,
+                plugins::plants::PlantPlugin,
+                plugins::projectile::ProjectilePlugin,
+                plugins::status::StatusPlugin,
+                toolbar::ToolbarPlugin,
+                plugins::replay::ReplayPlugin,
+            ))
+            .add_systems(PreStartup, PlayerTextureResources::setup)
+            .add_systems(
+                FixedPostUpdate,
+                remove_dying.run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                PostUpdate,
+                remove_dying.run_if(in_state(GameState::Running)),
+            )
+            .add_observer(plugins::player::dead_cleaner);
+    }
+}
+
 fn setup(mut commands: Commands) {
     commands.spawn(Camera2d);
 }
@@ -64,7 +126,8 @@ fn debug_setup(mut commands: Commands, textures: Res<PlayerTextureResources>) {}
 
 /// A component that marks an entity as dying
 ///
-/// It will be despawned at PostUpdate
+/// It will be despawned at the end of the tick, or at
+/// PostUpdate if it died outside of gameplay (e.g. clicked)
 #[derive(Component)]
 pub struct Dying;
 
@@ -0,0 +1,132 @@
+//! What's left on screen after a plant or zombie dies.
+//!
+//! A dead player is removed right away, so it stops eating, being
+//! eaten and being hit. A [Corpse] showing how it looked is left in
+//! its place, playing an animation picked from what killed it.
+
+use super::player::{DamageKind, Died};
+use crate::{Dying, GameState, PauseState};
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(leave_corpse);
+        app.add_systems(
+            Update,
+            animate_corpses.run_if(in_state(PauseState::Playing)),
+        );
+    }
+}
+
+/// How a corpse goes away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathAnimation {
+    /// Falls on its back and fades
+    FallOver,
+    /// Burnt black, then crumbles
+    Burnt,
+    /// Thrown off the lawn, spinning
+    Flung,
+    /// Eaten away to nothing
+    Shrink,
+}
+
+impl From<DamageKind> for DeathAnimation {
+    fn from(cause: DamageKind) -> Self {
+        match cause {
+            DamageKind::Projectile => DeathAnimation::FallOver,
+            DamageKind::Explosion | DamageKind::Burn => DeathAnimation::Burnt,
+            DamageKind::Mower => DeathAnimation::Flung,
+            DamageKind::Bite => DeathAnimation::Shrink,
+        }
+    }
+}
+
+impl DeathAnimation {
+    fn seconds(&self) -> f32 {
+        match self {
+            DeathAnimation::FallOver => 1.,
+            DeathAnimation::Burnt => 0.8,
+            DeathAnimation::Flung => 0.6,
+            DeathAnimation::Shrink => 0.5,
+        }
+    }
+}
+
+/// Looks like a dead player, and only plays its death animation
+#[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
+pub struct Corpse {
+    animation: DeathAnimation,
+    timer: Timer,
+    start: Transform,
+    color: Srgba,
+}
+
+fn leave_corpse(
+    trigger: Trigger<Died>,
+    mut commands: Commands,
+    players: Query<(&Transform, &Sprite)>,
+) {
+    // Nothing to show in the headless simulation
+    let Ok((transform, sprite)) = players.get(trigger.target()) else {
+        return;
+    };
+
+    let animation = DeathAnimation::from(trigger.cause);
+    commands.spawn((
+        Corpse {
+            animation,
+            timer: Timer::from_seconds(animation.seconds(), TimerMode::Once),
+            start: *transform,
+            color: sprite.color.to_srgba(),
+        },
+        sprite.clone(),
+        *transform,
+    ));
+}
+
+fn animate_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    corpses: Query<(Entity, &mut Corpse, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut corpse, mut transform, mut sprite) in corpses {
+        corpse.timer.tick(time.delta());
+        let progress = corpse.timer.fraction();
+        let mut color = corpse.color;
+        *transform = corpse.start;
+
+        match corpse.animation {
+            DeathAnimation::FallOver => {
+                // Zombies walk left, so they fall to the right
+                transform.rotate_z(-PI / 2. * progress.min(0.5) * 2.);
+                color.alpha *= 1. - progress;
+            }
+            DeathAnimation::Burnt => {
+                let burnt = (progress * 2.).min(1.);
+                color.red *= 1. - burnt * 0.9;
+                color.green *= 1. - burnt * 0.9;
+                color.blue *= 1. - burnt * 0.9;
+                color.alpha *= 1. - (progress * 2. - 1.).max(0.);
+            }
+            DeathAnimation::Flung => {
+                transform.translation.x += 400. * progress;
+                transform.translation.y += (PI * progress).sin() * 60.;
+                transform.rotate_z(-4. * PI * progress);
+                color.alpha *= 1. - progress;
+            }
+            DeathAnimation::Shrink => {
+                transform.scale *= 1. - progress;
+            }
+        }
+        sprite.color = color.into();
+
+        if corpse.timer.finished() {
+            commands.entity(entity).insert(Dying);
+        }
+    }
+}
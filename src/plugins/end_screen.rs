@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use super::rng::GameRng;
 use crate::GameState;
 
 pub struct EndScreen;
@@ -11,18 +12,19 @@ impl Plugin for EndScreen {
     }
 }
 
-fn show_win(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        Sprite::from_image(asset_server.load("savedgames.png")),
-        Text2d::new("You win"),
-        Transform::from_xyz(0., 0., 100.),
-    ));
+fn show_win(commands: Commands, asset_server: Res<AssetServer>, rng: Res<GameRng>) {
+    show_end_screen(commands, asset_server, "You win", rng.seed());
+}
+
+fn show_lose(commands: Commands, asset_server: Res<AssetServer>, rng: Res<GameRng>) {
+    show_end_screen(commands, asset_server, "You lose", rng.seed());
 }
 
-fn show_lose(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// The seed is shown so that the game can be played again with `--seed`
+fn show_end_screen(mut commands: Commands, asset_server: Res<AssetServer>, text: &str, seed: u64) {
     commands.spawn((
         Sprite::from_image(asset_server.load("savedgames.png")),
-        Text2d::new("You lose"),
+        Text2d::new(format!("{text}\nSeed: {seed}")),
         Transform::from_xyz(0., 0., 100.),
     ));
 }
@@ -0,0 +1,113 @@
+//! Running the game without a window, e.g. to simulate a level.
+//!
+//! The headless app is the game logic from [GamePlugin] on top of
+//! [MinimalPlugins]. No texture is loaded, sprites get placeholder
+//! handles instead. Nobody clicks in there, so the game is played by
+//! triggering [PlantAction](super::toolbar::PlantAction) and
+//! [CollectSun](super::plants::sunflower::CollectSun) on the world.
+//!
+//! Run it with `--headless` to simulate the level with nobody
+//! playing, or build one with [headless_app] and drive it with
+//! [App::update].
+
+use super::{
+    level::{CurrentLevel, LevelTime, LoadedLevel},
+    rng::GameRng,
+};
+use crate::{GamePlugin, GameState, TICKS_PER_SECOND};
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    asset::{AssetPath, LoadState},
+    ecs::system::SystemParam,
+    log::LogPlugin,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use core::time::Duration;
+
+/// The **Resource** marking that the game runs without a window
+#[derive(Resource)]
+pub struct Headless;
+
+/// System param to load textures, which only loads
+/// placeholders when running headless
+#[derive(SystemParam)]
+pub struct TextureLoader<'w> {
+    asset_server: Res<'w, AssetServer>,
+    headless: Option<Res<'w, Headless>>,
+}
+
+impl TextureLoader<'_> {
+    pub fn load<'a, A: Asset>(&self, path: impl Into<AssetPath<'a>>) -> Handle<A> {
+        match self.headless {
+            Some(_) => Handle::default(),
+            None => self.asset_server.load(path),
+        }
+    }
+}
+
+/// Build the game without a window.
+///
+/// It starts the level as soon as it's loaded, and each update
+/// simulates exactly one gameplay tick, however fast it runs.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        LogPlugin::default(),
+        AssetPlugin::default(),
+        StatesPlugin,
+    ))
+    .insert_resource(Headless)
+    .insert_resource(TimeUpdateStrategy::ManualDuration(
+        Time::<Fixed>::from_hz(TICKS_PER_SECOND).timestep(),
+    ))
+    .add_plugins(GamePlugin)
+    .add_systems(
+        Update,
+        start_when_loaded.run_if(in_state(GameState::WaitForStart)),
+    );
+    app
+}
+
+/// Simulate the level with nobody playing, and report how it ended
+pub fn run() {
+    headless_app()
+        .add_systems(OnEnter(GameState::End { win: true }), exit_on_end)
+        .add_systems(OnEnter(GameState::End { win: false }), exit_on_end)
+        .run();
+}
+
+fn start_when_loaded(
+    level: LoadedLevel,
+    current: Res<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if level.is_loaded() {
+        next_state.set(GameState::Running);
+    } else if let Some(LoadState::Failed(_)) = asset_server.get_load_state(&current.0) {
+        // Already logged by the level plugin
+        exit.write(AppExit::error());
+    }
+}
+
+fn exit_on_end(
+    state: Res<State<GameState>>,
+    level_time: Res<LevelTime>,
+    rng: Res<GameRng>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let GameState::End { win } = state.get() else {
+        return;
+    };
+    let result = if *win { "won" } else { "lost" };
+    info!(
+        "Level {result} after {:.1}s with seed {}",
+        level_time.0.as_secs_f32(),
+        rng.seed()
+    );
+    exit.write(AppExit::Success);
+}
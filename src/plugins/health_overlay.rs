@@ -0,0 +1,199 @@
+//! Health bars over plants and zombies, and floating numbers for
+//! the damage they take.
+//!
+//! Only drawn when [Settings::show_health] is on. Bars follow where
+//! their player is drawn, so they move as smoothly as it does.
+//! Armor counts as health here, so armored zombies are seen
+//! getting hurt before it falls off.
+
+use super::{
+    interpolation::interpolate,
+    player::{Damaged, PlayerCommon},
+    settings::Settings,
+    zombies::armor::Armor,
+    FLYING_Z,
+};
+use crate::{Dying, GameState, PauseState};
+use bevy::{prelude::*, sprite::Anchor};
+
+pub struct HealthOverlayPlugin;
+
+impl Plugin for HealthOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(add_health_bar);
+        app.add_observer(show_damage);
+        app.add_systems(
+            Update,
+            (
+                update_health_bars,
+                show_health_bars.run_if(resource_changed::<Settings>),
+            ),
+        );
+        app.add_systems(
+            Update,
+            float_damage_numbers.run_if(in_state(PauseState::Playing)),
+        );
+        app.add_systems(PostUpdate, follow_players.after(interpolate));
+    }
+}
+
+/// Above players and flying things, below the toolbar
+const HEALTH_OVERLAY_Z: f32 = FLYING_Z + 0.5;
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(40., 5.);
+/// Where bars are drawn, from the center of their player
+const HEALTH_BAR_OFFSET: Vec2 = Vec2::new(0., 45.);
+/// Pixels risen per second by damage numbers
+const DAMAGE_NUMBER_SPEED: f32 = 30.;
+const DAMAGE_NUMBER_SECONDS: f32 = 1.;
+
+/// Drawn at the position of a player, plus an offset
+#[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
+struct FollowPlayer {
+    player: Entity,
+    offset: Vec2,
+}
+
+/// The part of a health bar showing what's left
+#[derive(Component)]
+struct HealthBarFill {
+    /// Health and armor of the player when the bar was added
+    max: f32,
+}
+
+/// What a player can take before dying, armor included
+fn total_health(common: &PlayerCommon, armor: Option<&Armor>) -> f32 {
+    common.health + armor.map_or(0., |armor| armor.health)
+}
+
+#[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
+struct DamageNumber {
+    timer: Timer,
+}
+
+fn visibility(settings: &Settings) -> Visibility {
+    match settings.show_health {
+        true => Visibility::Inherited,
+        false => Visibility::Hidden,
+    }
+}
+
+fn add_health_bar(
+    trigger: Trigger<OnAdd, PlayerCommon>,
+    mut commands: Commands,
+    players: Query<(&PlayerCommon, Option<&Armor>)>,
+    settings: Res<Settings>,
+) {
+    let player = trigger.target();
+    let Ok((common, armor)) = players.get(player) else {
+        return;
+    };
+
+    commands.spawn((
+        FollowPlayer {
+            player,
+            offset: HEALTH_BAR_OFFSET,
+        },
+        Sprite::from_color(Color::srgb(0.2, 0.2, 0.2), HEALTH_BAR_SIZE),
+        visibility(&settings),
+    ));
+    commands.spawn((
+        FollowPlayer {
+            player,
+            offset: HEALTH_BAR_OFFSET - Vec2::new(HEALTH_BAR_SIZE.x / 2., 0.),
+        },
+        HealthBarFill {
+            max: total_health(common, armor),
+        },
+        Sprite {
+            anchor: Anchor::CenterLeft,
+            ..Sprite::from_color(Color::srgb(0.1, 0.9, 0.1), HEALTH_BAR_SIZE)
+        },
+        visibility(&settings),
+    ));
+}
+
+fn update_health_bars(
+    mut bars: Query<(&FollowPlayer, &HealthBarFill, &mut Sprite)>,
+    players: Query<(&PlayerCommon, Option<&Armor>), Or<(Changed<PlayerCommon>, Changed<Armor>)>>,
+) {
+    for (follow, fill, mut sprite) in &mut bars {
+        let Ok((common, armor)) = players.get(follow.player) else {
+            continue;
+        };
+        let left = (total_health(common, armor) / fill.max).clamp(0., 1.);
+        sprite.custom_size = Some(HEALTH_BAR_SIZE * Vec2::new(left, 1.));
+        sprite.color = Color::srgb(1. - left, left, 0.1);
+    }
+}
+
+fn show_health_bars(settings: Res<Settings>, bars: Query<&mut Visibility, With<FollowPlayer>>) {
+    for mut bar in bars {
+        *bar = visibility(&settings);
+    }
+}
+
+/// Draw bars where their player is drawn, and remove
+/// them with it
+fn follow_players(
+    mut commands: Commands,
+    bars: Query<(Entity, &FollowPlayer, &mut GlobalTransform), Without<Dying>>,
+    players: Query<&GlobalTransform, (With<PlayerCommon>, Without<FollowPlayer>, Without<Dying>)>,
+) {
+    for (entity, follow, mut global) in bars {
+        let Ok(player) = players.get(follow.player) else {
+            commands.entity(entity).insert(Dying);
+            continue;
+        };
+        let position = player.translation().xy() + follow.offset;
+        *global = Transform::from_translation(position.extend(HEALTH_OVERLAY_Z)).into();
+    }
+}
+
+fn show_damage(
+    trigger: Trigger<Damaged>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    players: Query<&Transform, With<PlayerCommon>>,
+) {
+    // Burns deal a little damage every tick, too small to show
+    let amount = (trigger.amount + trigger.absorbed).round();
+    if !settings.show_health || amount < 1. {
+        return;
+    }
+    let Ok(transform) = players.get(trigger.target()) else {
+        return;
+    };
+
+    let position = transform.translation.xy() + HEALTH_BAR_OFFSET;
+    commands.spawn((
+        DamageNumber {
+            timer: Timer::from_seconds(DAMAGE_NUMBER_SECONDS, TimerMode::Once),
+        },
+        Text2d::new(format!("{amount}")),
+        TextFont::from_font_size(16.),
+        // Grey when the armor took the whole hit
+        TextColor(match trigger.amount > 0. {
+            true => Color::srgb(1., 0.3, 0.3),
+            false => Color::srgb(0.8, 0.8, 0.8),
+        }),
+        Transform::from_translation(position.extend(HEALTH_OVERLAY_Z)),
+    ));
+}
+
+/// Damage numbers rise and fade away
+fn float_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut number, mut transform, mut color) in numbers {
+        number.timer.tick(time.delta());
+        transform.translation.y += DAMAGE_NUMBER_SPEED * time.delta_secs();
+        color.0.set_alpha(number.timer.fraction_remaining());
+        if number.timer.finished() {
+            commands.entity(entity).insert(Dying);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Drawing moving things smoothly between gameplay ticks.
+//!
+//! Gameplay runs in `FixedUpdate`, so positions only change
+//! [TICKS_PER_SECOND](crate::TICKS_PER_SECOND) times a second, which
+//! looks choppy on fast screens. Entities with [Interpolated] are drawn
+//! between their positions of the last two ticks instead. Only where
+//! they are drawn changes: `Transform` is still where they really are,
+//! so gameplay keeps reading it.
+
+use bevy::{
+    ecs::{component::HookContext, world::DeferredWorld},
+    prelude::*,
+};
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, remember_translation);
+        app.add_systems(
+            PostUpdate,
+            interpolate.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// A component for entities moved by gameplay, drawn between
+/// their last two positions.
+///
+/// Only for entities without a parent, as it
+/// overwrites their `GlobalTransform`.
+#[derive(Component, Default)]
+#[require(Transform)]
+#[component(on_add = interpolated_on_add)]
+pub struct Interpolated {
+    previous: Vec3,
+}
+
+impl Interpolated {
+    /// Draw the entity where it is, rather than sliding there
+    /// from where it was. For entities put somewhere at once,
+    /// e.g. when a save is restored.
+    pub fn teleported(mut entity: EntityWorldMut) {
+        let Some(transform) = entity.get::<Transform>() else {
+            return;
+        };
+        let translation = transform.translation;
+        if let Some(mut interpolated) = entity.get_mut::<Interpolated>() {
+            interpolated.previous = translation;
+        }
+    }
+}
+
+/// Start from where the entity is spawned, not from the origin
+fn interpolated_on_add(mut world: DeferredWorld, context: HookContext) {
+    let translation = world.get::<Transform>(context.entity).unwrap().translation;
+    world
+        .get_mut::<Interpolated>(context.entity)
+        .unwrap()
+        .previous = translation;
+}
+
+fn remember_translation(entities: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in entities {
+        interpolated.previous = transform.translation;
+    }
+}
+
+pub fn interpolate(
+    time: Res<Time<Fixed>>,
+    entities: Query<(&Transform, &Interpolated, &mut GlobalTransform)>,
+) {
+    let alpha = time.overstep_fraction();
+    for (transform, interpolated, mut global) in entities {
+        let translation = interpolated.previous.lerp(transform.translation, alpha);
+        *global = transform.with_translation(translation).into();
+    }
+}
@@ -0,0 +1,212 @@
+//! Playing without a mouse.
+//!
+//! Number keys pick the cards of the toolbar from the left, arrows
+//! or WASD move a [GridCursor] over the land, Enter plants the
+//! picked card on it and Space collects the sun nearest to it.
+//!
+//! Keys trigger the same [PickCard], [PlantAction] and [CollectSun]
+//! as clicks do, so the same rules apply and replays record them
+//! the same way.
+
+use super::{
+    land::{LandPlants, LAND_TILE_SIZE},
+    level::LoadedLevel,
+    plants::sunflower::{CollectSun, Sun},
+    toolbar::{PickCard, PlacementGhost, PlantAction, ToolbarPlant},
+    GridPos, FLYING_Z,
+};
+use crate::{Dying, GameState, PauseState};
+use bevy::prelude::*;
+
+pub struct KeyboardPlugin;
+
+impl Plugin for KeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridCursor>();
+        app.add_systems(OnEnter(GameState::Running), add_cursor);
+        app.add_systems(
+            Update,
+            (
+                (mouse_moved, move_cursor).before(show_cursor),
+                pick_card_with_keys,
+                plant_at_cursor,
+                collect_nearest_sun,
+                show_cursor.run_if(resource_changed::<GridCursor>),
+            )
+                .run_if(in_state(PauseState::Playing)),
+        );
+    }
+}
+
+/// Keys picking the cards of the toolbar, from the left
+const CARD_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+const CURSOR_COLOR: Color = Color::srgba(1., 0.9, 0.2, 0.35);
+/// Over plants and zombies, under suns
+const CURSOR_Z: f32 = FLYING_Z - 0.1;
+
+/// The **Resource** holding the tile picked with the keyboard
+#[derive(Resource, Default)]
+pub struct GridCursor {
+    pub tile: (i32, i32),
+    /// The keyboard was used after the mouse, so the cursor
+    /// is shown and picked cards go on its tile
+    pub active: bool,
+}
+
+/// Marker component for the tile showing where the [GridCursor] is
+#[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
+struct CursorHighlight;
+
+fn add_cursor(mut commands: Commands, level: LoadedLevel) {
+    // Start on the middle row
+    let rows = level.get().land_size.1;
+    commands.insert_resource(GridCursor {
+        tile: (0, rows as i32 / 2),
+        active: false,
+    });
+    commands.spawn((
+        CursorHighlight,
+        Sprite::from_color(CURSOR_COLOR, LAND_TILE_SIZE),
+        Transform::from_xyz(0., 0., CURSOR_Z),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+    ));
+}
+
+/// Hand over to the mouse as soon as it moves
+fn mouse_moved(mut moves: EventReader<CursorMoved>, mut cursor: ResMut<GridCursor>) {
+    if moves.read().count() > 0 && cursor.active {
+        cursor.active = false;
+    }
+}
+
+fn move_cursor(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cursor: ResMut<GridCursor>,
+    map: Res<LandPlants>,
+) {
+    let mut step = IVec2::ZERO;
+    if keys.any_just_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) {
+        step.x -= 1;
+    }
+    if keys.any_just_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) {
+        step.x += 1;
+    }
+    // Rows are counted from the bottom
+    if keys.any_just_pressed([KeyCode::ArrowUp, KeyCode::KeyW]) {
+        step.y += 1;
+    }
+    if keys.any_just_pressed([KeyCode::ArrowDown, KeyCode::KeyS]) {
+        step.y -= 1;
+    }
+    if step == IVec2::ZERO {
+        return;
+    }
+
+    // Only over the tiles of the level
+    let (x, y) = cursor.tile;
+    let (columns, rows) = map.size;
+    cursor.tile = (
+        (x + step.x).clamp(0, columns as i32 - 1),
+        (y + step.y).clamp(0, rows as i32 - 1),
+    );
+    cursor.active = true;
+}
+
+fn show_cursor(
+    cursor: Res<GridCursor>,
+    highlight: Single<(&mut Transform, &mut Visibility), With<CursorHighlight>>,
+) {
+    let (mut transform, mut visibility) = highlight.into_inner();
+    let position = GridPos::new(cursor.tile.0, cursor.tile.1).to_world();
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+    *visibility = match cursor.active {
+        true => Visibility::Inherited,
+        false => Visibility::Hidden,
+    };
+}
+
+fn pick_card_with_keys(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cursor: ResMut<GridCursor>,
+    cards: Query<(Entity, &Transform), With<ToolbarPlant>>,
+) {
+    let Some(index) = CARD_KEYS.iter().position(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+    let mut cards: Vec<_> = cards.iter().collect();
+    cards.sort_by(|(_, a), (_, b)| a.translation.x.total_cmp(&b.translation.x));
+
+    if let Some((card, _)) = cards.get(index) {
+        commands.trigger_targets(PickCard, *card);
+        cursor.active = true;
+    }
+}
+
+/// Plant the picked card on the [GridCursor] tile with Enter.
+///
+/// The card stays picked when the tile is taken,
+/// so the cursor can be moved on to another one.
+fn plant_at_cursor(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cursor: ResMut<GridCursor>,
+    ghosts: Query<(Entity, &PlacementGhost)>,
+    map: Res<LandPlants>,
+) {
+    if !keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+        return;
+    }
+    cursor.active = true;
+
+    let tile = GridPos::new(cursor.tile.0, cursor.tile.1);
+    if !map.is_empty(tile) {
+        return;
+    }
+    for (entity, ghost) in ghosts {
+        commands.trigger(PlantAction {
+            kind: ghost.kind,
+            tile: cursor.tile,
+        });
+        commands.entity(entity).insert(Dying);
+    }
+}
+
+/// Collect the sun nearest to the [GridCursor] with Space
+fn collect_nearest_sun(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cursor: ResMut<GridCursor>,
+    suns: Query<(Entity, &Sun, &Transform), Without<Dying>>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    cursor.active = true;
+
+    let from = GridPos::new(cursor.tile.0, cursor.tile.1).to_world();
+    let nearest =
+        suns.iter()
+            .filter(|(_, sun, _)| sun.collectable())
+            .min_by(|(_, _, a), (_, _, b)| {
+                let a = a.translation.xy().distance_squared(from);
+                let b = b.translation.xy().distance_squared(from);
+                a.total_cmp(&b)
+            });
+    if let Some((sun, _, _)) = nearest {
+        commands.trigger_targets(CollectSun, sun);
+    }
+}
@@ -8,6 +8,8 @@ use std::fmt::Debug;
 
 use crate::GameState;
 
+use super::headless::TextureLoader;
+use super::level::LoadedLevel;
 use super::toolbar::SunCount;
 
 pub const LAND_DISPLAY_OFFSET: Vec2 = Vec2::new(70.0, 0.0);
@@ -26,13 +28,21 @@ fn setup_resources(mut commands: Commands) {
 }
 
 #[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
 struct Land;
 
-fn add_land(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn add_land(
+    mut commands: Commands,
+    asset_server: TextureLoader,
+    level: LoadedLevel,
+    mut map: ResMut<LandPlants>,
+) {
+    let level = level.get();
+    map.size = level.land_size;
     commands.spawn((
         Land,
         Sprite {
-            image: asset_server.load("Background1.png"),
+            image: asset_server.load(&level.background),
             ..default()
         },
         Transform::from_xyz(LAND_DISPLAY_OFFSET.x, LAND_DISPLAY_OFFSET.y, -1.0),
@@ -150,9 +160,20 @@ impl From<Vec2> for GridPos {
 }
 
 /// Map index storing plants on each tile
-#[derive(Resource, Default)]
+#[derive(Resource)]
 pub struct LandPlants {
     pub tiles: HashMap<(i32, i32), Entity>,
+    /// How many columns and rows can be planted on in this level
+    pub size: (u32, u32),
+}
+
+impl Default for LandPlants {
+    fn default() -> Self {
+        LandPlants {
+            tiles: HashMap::default(),
+            size: (LAND_SIZE.x as u32, LAND_SIZE.y as u32),
+        }
+    }
 }
 
 impl LandPlants {
@@ -165,6 +186,9 @@ impl LandPlants {
             return false;
         }
         let key: (i32, i32) = pos.into();
+        if key.0 >= self.size.0 as i32 || key.1 >= self.size.1 as i32 {
+            return false;
+        }
         !self.tiles.get(&key).is_some()
     }
 
@@ -0,0 +1,126 @@
+//! Lawnmowers, the last line of defense on each row.
+//!
+//! When a zombie reaches the house, the mower on that row drives
+//! across it and kills every zombie on its way. Each mower can only
+//! be used once, and the game is lost when a zombie reaches a row
+//! without a mower.
+
+use super::{
+    headless::TextureLoader,
+    interpolation::Interpolated,
+    land::LAND_SIZE,
+    level::LoadedLevel,
+    player::{DamageKind, Damaged, PlayerCommon},
+    zombies::{
+        armor::{damage_with_armor, Armor},
+        LandZombies, ZombieCommon,
+    },
+    GridPos, PLAYERS_Z,
+};
+use crate::{Dying, GameState, PauseState};
+use bevy::prelude::*;
+
+pub struct LawnmowerPlugin;
+
+impl Plugin for LawnmowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Running), add_lawnmowers);
+        app.add_systems(
+            FixedUpdate,
+            drive_lawnmowers.run_if(in_state(PauseState::Playing)),
+        );
+        app.add_observer(zombie_reached_house);
+    }
+}
+
+/// Triggered when a zombie walks past the left edge of the land
+#[derive(Event)]
+pub struct ZombieReachedHouse {
+    pub row: usize,
+}
+
+#[derive(Component)]
+#[require(Interpolated, StateScoped<GameState> = StateScoped(GameState::Running))]
+pub struct Lawnmower {
+    pub row: usize,
+    pub driving: bool,
+}
+
+/// Damage dealt to zombies run over by a mower, enough to kill any of them
+const MOWER_DAMAGE: f32 = 10000.;
+/// Pixels driven per second
+const MOWER_SPEED: f32 = 500.;
+
+fn add_lawnmowers(mut commands: Commands, asset_server: TextureLoader, level: LoadedLevel) {
+    let image = asset_server.load("Lawnmover.png");
+    for row in 0..level.get().rows() as usize {
+        let pos = GridPos::new(-1, row);
+        commands.spawn((
+            Lawnmower {
+                row,
+                driving: false,
+            },
+            Sprite::from_image(image.clone()),
+            pos.to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5)
+                .with_scale(Vec3::splat(0.3)),
+        ));
+    }
+}
+
+/// Start the mower on the row, or lose the game if it's already used
+fn zombie_reached_house(
+    trigger: Trigger<ZombieReachedHouse>,
+    mut mowers: Query<&mut Lawnmower>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let row = trigger.event().row;
+    match mowers.iter_mut().find(|mower| mower.row == row) {
+        Some(mut mower) => {
+            if !mower.driving {
+                mower.driving = true;
+            }
+        }
+        None => next_state.set(GameState::End { win: false }),
+    }
+}
+
+/// Move driving mowers to the right and kill zombies they run over
+fn drive_lawnmowers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mowers: Query<(Entity, &mut Transform, &Lawnmower)>,
+    land_zombies: Res<LandZombies>,
+    mut zombies: Query<
+        (&Transform, &mut PlayerCommon, Option<&mut Armor>),
+        (With<ZombieCommon>, Without<Lawnmower>),
+    >,
+) {
+    for (entity, mut position, mower) in mowers {
+        if !mower.driving {
+            continue;
+        }
+        position.translation.x += time.delta_secs() * MOWER_SPEED;
+
+        let mower_pos: GridPos = (*position).into();
+        for zombie in &land_zombies.rows[mower.row] {
+            let Ok((zombie_pos, mut health, armor)) = zombies.get_mut(*zombie) else {
+                continue;
+            };
+            if GridPos::from(*zombie_pos).x <= mower_pos.x + 0.5 {
+                damage_with_armor(
+                    &mut commands,
+                    *zombie,
+                    &mut health,
+                    armor,
+                    Damaged::new(MOWER_DAMAGE, DamageKind::Mower, entity),
+                );
+            }
+        }
+
+        // Zombies are spawned at LAND_SIZE.x + 2, so drive
+        // past that before the mower is gone for good
+        if mower_pos.x > LAND_SIZE.x + 3. {
+            commands.entity(entity).insert(Dying);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Levels, loaded from `*.level.ron` files in the assets folder.
+//!
+//! A level declares the land, the starting suns, the plants
+//! available in the toolbar and the zombies it sends, so new
+//! levels can be added without recompiling.
+
+use super::{
+    land::LAND_SIZE,
+    plants::PlantKind,
+    zombies::{create_zombie::ZombieType, registry::ZombieRegistry},
+};
+use crate::{GameState, PauseState};
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use core::time::Duration;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>();
+        app.register_asset_loader(LevelLoader);
+        app.init_resource::<LevelTime>();
+        app.add_systems(PreStartup, load_level);
+        app.add_systems(OnEnter(GameState::Running), reset_level_time);
+        app.add_systems(
+            Update,
+            report_level_errors.run_if(in_state(GameState::WaitForStart)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            tick_level_time.run_if(in_state(PauseState::Playing)),
+        );
+    }
+}
+
+#[cfg(not(feature = "debug_mode"))]
+const LEVEL_PATH: &str = "levels/1.level.ron";
+#[cfg(feature = "debug_mode")]
+const LEVEL_PATH: &str = "levels/debug.level.ron";
+
+/// How many cards fit in the toolbar, next to the shovel
+pub const MAX_CARDS: usize = 4;
+
+/// Everything that defines a level
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Level {
+    /// How many columns and rows of the land can be planted on
+    pub land_size: (u32, u32),
+    /// Image of the land, relative to the assets folder
+    pub background: String,
+    pub initial_suns: i32,
+    /// Seconds before the first wave, to plant some sunflowers
+    pub first_wave_delay: f32,
+    /// Seconds between two waves
+    pub wave_interval: f32,
+    pub total_waves: u32,
+    /// Every N-th wave is a huge wave
+    pub huge_wave_every: u32,
+    /// Zombies that may show up in this level, at least one of
+    /// them sent in waves. Zombies leading huge waves (e.g. Flag)
+    /// are only sent if they are listed.
+    pub zombies: Vec<ZombieType>,
+    /// Plants in the toolbar, from left to right
+    pub cards: Vec<PlantKind>,
+    /// Fraction of its price given back when a plant is dug up
+    /// with the shovel, from 0 to 1. No refund if not given.
+    #[serde(default)]
+    pub shovel_refund: f32,
+    /// Seed for the random decisions, random if not given.
+    /// Overridden by `--seed` on the command line.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Problems found in a level file that parsed correctly
+#[derive(Debug, Error)]
+pub enum LevelError {
+    #[error("land_size {0:?} must be between (1, 1) and {1:?}")]
+    LandSize((u32, u32), (u32, u32)),
+    #[error("background must not be empty")]
+    NoBackground,
+    #[error("initial_suns must not be negative, got {0}")]
+    NegativeSuns(i32),
+    #[error("first_wave_delay must not be negative, got {0}")]
+    NegativeDelay(f32),
+    #[error("wave_interval must be positive, got {0}")]
+    WaveInterval(f32),
+    #[error("total_waves must be at least 1")]
+    NoWaves,
+    #[error("huge_wave_every must be at least 1")]
+    HugeWaveEvery,
+    #[error("zombies must list at least one zombie sent in waves, not only leaders")]
+    NoZombies,
+    #[error("cards must list between 1 and {MAX_CARDS} plants, got {0}")]
+    CardCount(usize),
+    #[error("{0:?} is listed more than once in cards")]
+    DuplicateCard(PlantKind),
+    #[error("shovel_refund must be between 0 and 1, got {0}")]
+    ShovelRefund(f32),
+}
+
+impl Level {
+    pub fn validate(&self) -> Result<(), LevelError> {
+        let max_size = (LAND_SIZE.x as u32, LAND_SIZE.y as u32);
+        let (columns, rows) = self.land_size;
+        if columns == 0 || rows == 0 || columns > max_size.0 || rows > max_size.1 {
+            return Err(LevelError::LandSize(self.land_size, max_size));
+        }
+        if self.background.is_empty() {
+            return Err(LevelError::NoBackground);
+        }
+        if self.initial_suns < 0 {
+            return Err(LevelError::NegativeSuns(self.initial_suns));
+        }
+        if self.first_wave_delay < 0. {
+            return Err(LevelError::NegativeDelay(self.first_wave_delay));
+        }
+        if self.wave_interval <= 0. {
+            return Err(LevelError::WaveInterval(self.wave_interval));
+        }
+        if self.total_waves == 0 {
+            return Err(LevelError::NoWaves);
+        }
+        if self.huge_wave_every == 0 {
+            return Err(LevelError::HugeWaveEvery);
+        }
+        // Leaders alone (e.g. Flag) would leave every wave empty
+        let registry = ZombieRegistry::default();
+        if !self
+            .zombies
+            .iter()
+            .any(|kind| registry.get(*kind).weight > 0)
+        {
+            return Err(LevelError::NoZombies);
+        }
+        if self.cards.is_empty() || self.cards.len() > MAX_CARDS {
+            return Err(LevelError::CardCount(self.cards.len()));
+        }
+        for (i, card) in self.cards.iter().enumerate() {
+            if self.cards[..i].contains(card) {
+                return Err(LevelError::DuplicateCard(*card));
+            }
+        }
+        if !(0. ..=1.).contains(&self.shovel_refund) {
+            return Err(LevelError::ShovelRefund(self.shovel_refund));
+        }
+        Ok(())
+    }
+
+    /// Rows zombies can walk on
+    pub fn rows(&self) -> u32 {
+        self.land_size.1
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse level file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid level: {0}")]
+    Invalid(#[from] LevelError),
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Level, LevelLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let level: Level = ron::de::from_bytes(&bytes)?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// The **Resource** holding the level being played
+#[derive(Resource)]
+pub struct CurrentLevel(pub Handle<Level>);
+
+fn load_level(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CurrentLevel(asset_server.load(LEVEL_PATH)));
+}
+
+/// The **Resource** counting how long the level has been played,
+/// not including the time spent paused
+#[derive(Resource, Default)]
+pub struct LevelTime(pub Duration);
+
+fn reset_level_time(mut commands: Commands) {
+    commands.insert_resource(LevelTime::default());
+}
+
+fn tick_level_time(time: Res<Time>, mut level_time: ResMut<LevelTime>) {
+    level_time.0 += time.delta();
+}
+
+/// Log why the level can't be played, once
+fn report_level_errors(
+    asset_server: Res<AssetServer>,
+    current: Res<CurrentLevel>,
+    mut reported: Local<bool>,
+) {
+    if *reported {
+        return;
+    }
+    if let Some(bevy::asset::LoadState::Failed(err)) = asset_server.get_load_state(&current.0) {
+        error!("Level {LEVEL_PATH} can't be played: {err}");
+        *reported = true;
+    }
+}
+
+/// System param to access the level being played.
+///
+/// The game only starts after the level is loaded,
+/// so it's always available while running.
+#[derive(SystemParam)]
+pub struct LoadedLevel<'w> {
+    current: Res<'w, CurrentLevel>,
+    levels: Res<'w, Assets<Level>>,
+}
+
+impl LoadedLevel<'_> {
+    pub fn is_loaded(&self) -> bool {
+        self.levels.contains(&self.current.0)
+    }
+
+    pub fn get(&self) -> &Level {
+        self.levels
+            .get(&self.current.0)
+            .expect("The level is loaded before the game starts")
+    }
+}
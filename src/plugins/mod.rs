@@ -14,13 +14,28 @@ const TOOLBAR_Z: f32 = 8.;
 /// adding plant
 const FLOATING_Z: f32 = 9.;
 
+pub mod death;
+pub mod headless;
+pub mod health_overlay;
+pub mod interpolation;
+pub mod keyboard;
 pub mod land;
+pub mod lawnmower;
+pub mod level;
 pub mod plants;
 pub mod player;
+pub mod progress_bar;
+pub mod projectile;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod settings;
+pub mod status;
 pub mod toolbar;
 pub mod zombies;
 
 pub mod end_screen;
+pub mod pause_menu;
 pub mod start_screen;
 
 pub use land::GridPos;
@@ -0,0 +1,184 @@
+//! The pause button, and the menu shown while the game is paused.
+//!
+//! Gameplay systems only run in [PauseState::Playing], and the
+//! virtual clock is stopped while paused, so nothing moves or
+//! cools down. The menu covers the whole screen, which blocks
+//! clicks to the land and the toolbar.
+
+use super::{save::SaveEvent, settings::Settings, toolbar::ToolbarSelection, TOOLBAR_Z};
+use crate::{GameState, PauseState, SCREEN_RESOLUTION};
+use bevy::{ecs::system::IntoObserverSystem, prelude::*};
+
+pub struct PauseMenu;
+
+impl Plugin for PauseMenu {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Running), add_pause_button);
+        app.add_systems(OnEnter(GameState::Restarting), restart);
+        app.add_systems(OnEnter(PauseState::Paused), (pause_time, show_pause_menu));
+        app.add_systems(OnExit(PauseState::Paused), unpause_time);
+        app.add_systems(Update, toggle_pause.run_if(in_state(GameState::Running)));
+    }
+}
+
+/// Above everything in the game, below the end screen
+const PAUSE_MENU_Z: f32 = 50.;
+
+fn add_pause_button(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            StateScoped(GameState::Running),
+            Sprite {
+                image: asset_server.load("pause_menu.png"),
+                custom_size: Some(Vec2::new(60., 60.)),
+                ..default()
+            },
+            Transform::from_xyz(400. - 40., 300. - 35., TOOLBAR_Z),
+            Pickable::default(),
+        ))
+        .observe(
+            |_trigger: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<PauseState>>| {
+                next_state.set(PauseState::Paused);
+            },
+        );
+}
+
+/// Pause or resume with Esc.
+///
+/// Esc drops a [ToolbarSelection] first, so it only pauses
+/// when nothing is picked from the toolbar.
+fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+    selections: Query<(), With<ToolbarSelection>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if *state.get() == PauseState::Playing && !selections.is_empty() {
+        return;
+    }
+    next_state.set(match state.get() {
+        PauseState::Playing => PauseState::Paused,
+        PauseState::Paused => PauseState::Playing,
+    });
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn show_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    // Dims the game and catches every click that would reach it
+    commands.spawn((
+        StateScoped(PauseState::Paused),
+        Sprite::from_color(Color::srgba(0., 0., 0., 0.5), SCREEN_RESOLUTION),
+        Transform::from_xyz(0., 0., PAUSE_MENU_Z),
+        Pickable::default(),
+    ));
+
+    commands.spawn((
+        StateScoped(PauseState::Paused),
+        Sprite {
+            image: asset_server.load("pause_background.png"),
+            custom_size: Some(Vec2::new(300., 380.)),
+            ..default()
+        },
+        Transform::from_xyz(0., 0., PAUSE_MENU_Z + 1.),
+        Pickable::default(),
+    ));
+
+    let button = asset_server.load("menu-button.png");
+    add_menu_button(&mut commands, button.clone(), "Resume", 140., resume);
+    add_menu_button(&mut commands, button.clone(), "Save", 70., save_clicked);
+    add_menu_button(
+        &mut commands,
+        button.clone(),
+        health_label(&settings),
+        0.,
+        toggle_health,
+    );
+    add_menu_button(
+        &mut commands,
+        button.clone(),
+        "Restart",
+        -70.,
+        restart_clicked,
+    );
+    add_menu_button(&mut commands, button, "Main Menu", -140., main_menu);
+}
+
+fn health_label(settings: &Settings) -> &'static str {
+    match settings.show_health {
+        true => "Health: On",
+        false => "Health: Off",
+    }
+}
+
+fn add_menu_button<M>(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    text: &str,
+    y: f32,
+    on_click: impl IntoObserverSystem<Pointer<Click>, (), M>,
+) {
+    commands
+        .spawn((
+            StateScoped(PauseState::Paused),
+            Sprite {
+                image,
+                custom_size: Some(Vec2::new(180., 63.)),
+                ..default()
+            },
+            Transform::from_xyz(0., y, PAUSE_MENU_Z + 2.),
+            Pickable::default(),
+        ))
+        .with_child((Text2d::new(text), Transform::from_xyz(0., 0., 0.1)))
+        .observe(on_click);
+}
+
+fn resume(_trigger: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<PauseState>>) {
+    next_state.set(PauseState::Playing);
+}
+
+fn save_clicked(_trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(SaveEvent);
+}
+
+fn toggle_health(
+    trigger: Trigger<Pointer<Click>>,
+    mut settings: ResMut<Settings>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text2d>,
+) {
+    settings.show_health = !settings.show_health;
+    for child in children.iter_descendants(trigger.target()) {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.0 = health_label(&settings).into();
+        }
+    }
+}
+
+fn restart_clicked(
+    _trigger: Trigger<Pointer<Click>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::Restarting);
+}
+
+fn main_menu(_trigger: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::WaitForStart);
+}
+
+fn restart(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Running);
+}
@@ -0,0 +1,96 @@
+use crate::{
+    plugins::{
+        land::LandPlants,
+        player::{DamageKind, Damaged, PlayerCommon},
+        status::{StatusEffect, StatusKind},
+        zombies::{
+            armor::{damage_with_armor, Armor},
+            LandZombies, ZombieCommon,
+        },
+        GridPos,
+    },
+    Dying,
+};
+use bevy::prelude::*;
+use core::time::Duration;
+
+/// How long it takes from planting to the explosion
+const FUSE_TIME: Duration = Duration::from_millis(1200);
+/// Damage dealt to every zombie in range, enough to kill any of them
+const EXPLOSION_DAMAGE: f32 = 1800.;
+/// How many tiles the explosion reaches in each direction
+const EXPLOSION_RANGE: i32 = 1;
+/// How many tiles away zombies catch fire, out of the explosion
+const BURN_RANGE: i32 = 2;
+/// Health lost per second by zombies on fire
+const BURN_DAMAGE: f32 = 10.;
+const BURN_SECONDS: f32 = 3.;
+
+#[derive(Component)]
+pub struct CherryBomb {
+    pub fuse_timer: Timer,
+}
+
+impl CherryBomb {
+    pub fn new() -> Self {
+        CherryBomb {
+            fuse_timer: Timer::new(FUSE_TIME, TimerMode::Once),
+        }
+    }
+}
+
+impl Default for CherryBomb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Explode cherry bombs whose fuse has burnt out.
+///
+/// Every zombie in the 3x3 tiles around the bomb is damaged, and
+/// those in the tiles around them catch fire. Then the bomb
+/// removes itself, which frees its land tile.
+pub fn cherrybomb_explode(
+    mut commands: Commands,
+    time: Res<Time>,
+    bombs: Query<(Entity, &Transform, &mut CherryBomb)>,
+    land_zombies: Res<LandZombies>,
+    map: Res<LandPlants>,
+    mut zombies: Query<(&Transform, &mut PlayerCommon, Option<&mut Armor>), With<ZombieCommon>>,
+) {
+    for (entity, transform, mut bomb) in bombs {
+        bomb.fuse_timer.tick(time.delta());
+        if !bomb.fuse_timer.finished() {
+            continue;
+        }
+
+        let (bomb_x, bomb_y): (i32, i32) = GridPos::from(*transform).into();
+        for row in (bomb_y - BURN_RANGE)..=(bomb_y + BURN_RANGE) {
+            if row < 0 || row >= map.size.1 as i32 {
+                continue;
+            }
+
+            for zombie in &land_zombies.rows[row as usize] {
+                let Ok((zombie_pos, mut health, armor)) = zombies.get_mut(*zombie) else {
+                    continue;
+                };
+                let (zombie_x, _): (i32, i32) = GridPos::from(*zombie_pos).into();
+                let distance = (zombie_x - bomb_x).abs().max((row - bomb_y).abs());
+                if distance <= EXPLOSION_RANGE {
+                    damage_with_armor(
+                        &mut commands,
+                        *zombie,
+                        &mut health,
+                        armor,
+                        Damaged::new(EXPLOSION_DAMAGE, DamageKind::Explosion, entity),
+                    );
+                } else if distance <= BURN_RANGE {
+                    StatusEffect::new(StatusKind::Burning(BURN_DAMAGE), BURN_SECONDS)
+                        .apply_to(&mut commands.entity(*zombie));
+                }
+            }
+        }
+
+        commands.entity(entity).insert(Dying);
+    }
+}
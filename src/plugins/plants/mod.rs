@@ -1,35 +1,58 @@
+pub mod cherrybomb;
 pub mod peashooter;
+pub mod registry;
 pub mod sunflower;
 pub mod wallnut;
 
 use super::{land::LandPlants, GridPos};
-use crate::{plugins::player::PlayerCommon, GameState};
+use crate::{plugins::player::PlayerCommon, GameState, PauseState};
 use bevy::{
     ecs::{component::HookContext, world::DeferredWorld},
     prelude::*,
 };
-use peashooter::{move_bullet, shoot};
-use sunflower::{gen_sun_from_sky, init_global_sun_res, move_sun, sunflow_gen_sun};
+use cherrybomb::cherrybomb_explode;
+use peashooter::shoot;
+use registry::PlantRegistry;
+use serde::{Deserialize, Serialize};
+use sunflower::{collect_sun, gen_sun_from_sky, init_global_sun_res, move_sun, sunflow_gen_sun};
 
 pub struct PlantPlugin;
 
 impl Plugin for PlantPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, init_global_sun_res);
+        app.add_systems(PreStartup, PlantRegistry::setup);
+        app.add_systems(OnEnter(GameState::Running), init_global_sun_res);
+        app.add_observer(collect_sun);
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
                 move_sun,
                 sunflow_gen_sun,
                 gen_sun_from_sky,
                 shoot,
-                move_bullet,
+                cherrybomb_explode,
             )
-                .run_if(in_state(GameState::Running)),
+                .run_if(in_state(PauseState::Playing)),
         );
     }
 }
 
+/// The ID of a plant, how the toolbar, level files and
+/// save files refer to it. See [PlantRegistry] for the rest.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlantKind {
+    Sunflower,
+    Peashooter,
+    Wallnut,
+    CherryBomb,
+}
+
+/// How many suns were paid for a plant
+///
+/// Used to refund suns when the plant is dug up
+#[derive(Component)]
+pub struct PlantPrice(pub i32);
+
 #[derive(Component)]
 #[component(on_add=plant_comm_on_add, on_remove=plant_comm_on_remove)]
 #[require(PlayerCommon, Transform)]
@@ -0,0 +1,134 @@
+//! Every plant of the game, keyed by its ID, the [PlantKind].
+//!
+//! A [PlantDefinition] holds everything the toolbar, the land and the
+//! save files need to know about a plant. Adding a plant is adding a
+//! [PlantKind], the component for its behavior and an entry here.
+
+use super::{
+    cherrybomb::CherryBomb, peashooter::Peashooter, sunflower::Sunflower, wallnut::Wallnut,
+    PlantCommon, PlantKind,
+};
+use crate::plugins::{
+    headless::TextureLoader, land::LAND_SIZE, player::PlayerCommon, GridPos, PLAYERS_Z,
+};
+use bevy::{platform::collections::HashMap, prelude::*};
+use vleue_kinetoscope::{AnimatedImage, AnimatedImageController};
+
+/// How a plant looks on the land
+pub enum PlantAppearance {
+    Animated(Handle<AnimatedImage>),
+    Still(Handle<Image>),
+}
+
+pub struct PlantDefinition {
+    pub name: &'static str,
+    /// Image of the card in the toolbar
+    pub card: Handle<Image>,
+    pub appearance: PlantAppearance,
+    pub price: i32,
+    /// Seconds before the card can be used again
+    pub recharge: f32,
+    pub health: f32,
+    /// Inserts the components that make the plant do its thing
+    pub behavior: fn(&mut EntityCommands),
+}
+
+/// The **Resource** holding the definition of every plant
+#[derive(Resource)]
+pub struct PlantRegistry(HashMap<PlantKind, PlantDefinition>);
+
+impl PlantRegistry {
+    pub fn setup(mut commands: Commands, asset_server: TextureLoader) {
+        let mut plants = HashMap::default();
+        plants.insert(
+            PlantKind::Sunflower,
+            PlantDefinition {
+                name: "Sunflower",
+                card: asset_server.load("SunflowerCard.PNG"),
+                appearance: PlantAppearance::Animated(asset_server.load("SunFlower.gif")),
+                price: 50,
+                recharge: 5.,
+                health: 100.,
+                behavior: |entity| {
+                    entity.insert(Sunflower::new());
+                },
+            },
+        );
+        plants.insert(
+            PlantKind::Peashooter,
+            PlantDefinition {
+                name: "Peashooter",
+                card: asset_server.load("PeashooterCard.PNG"),
+                appearance: PlantAppearance::Animated(asset_server.load("PeaShooter.gif")),
+                price: 100,
+                recharge: 10.,
+                health: 100.,
+                behavior: |entity| {
+                    entity.insert(Peashooter::new());
+                },
+            },
+        );
+        plants.insert(
+            PlantKind::Wallnut,
+            PlantDefinition {
+                name: "Wallnut",
+                card: asset_server.load("WallNutCard.png"),
+                appearance: PlantAppearance::Still(asset_server.load("Wall-nut1.png")),
+                price: 50,
+                recharge: 5.,
+                health: 250.,
+                behavior: |entity| {
+                    entity.insert(Wallnut);
+                },
+            },
+        );
+        plants.insert(
+            PlantKind::CherryBomb,
+            PlantDefinition {
+                name: "Cherry Bomb",
+                card: asset_server.load("CherryBombCard.png"),
+                appearance: PlantAppearance::Still(asset_server.load("CherryBomb.png")),
+                price: 150,
+                recharge: 30.,
+                health: 100.,
+                behavior: |entity| {
+                    entity.insert(CherryBomb::new());
+                },
+            },
+        );
+        commands.insert_resource(PlantRegistry(plants));
+    }
+
+    pub fn get(&self, kind: PlantKind) -> &PlantDefinition {
+        self.0
+            .get(&kind)
+            .expect("Every plant kind has a definition")
+    }
+
+    /// Put a plant on the tile at `pos`, without checking
+    /// whether the tile is empty
+    pub fn spawn(
+        &self,
+        kind: PlantKind,
+        pos: impl Into<GridPos>,
+        commands: &mut Commands,
+    ) -> Entity {
+        let definition = self.get(kind);
+        let pos: GridPos = pos.into();
+        let mut entity = commands.spawn((
+            PlayerCommon::new(definition.name, definition.health),
+            PlantCommon,
+            kind,
+            pos.round()
+                .to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y),
+        ));
+        match &definition.appearance {
+            PlantAppearance::Animated(animation) => {
+                entity.insert(AnimatedImageController::play(animation.clone()))
+            }
+            PlantAppearance::Still(image) => entity.insert(Sprite::from_image(image.clone())),
+        };
+        (definition.behavior)(&mut entity);
+        entity.id()
+    }
+}
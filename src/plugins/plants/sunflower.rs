@@ -1,93 +1,277 @@
-use super::{super::PLAYERS_Z, PlantCommon};
 use crate::{
     plugins::{
-        land::{LAND_SIZE, LAND_TILE_SIZE},
-        player::PlayerCommon,
+        interpolation::Interpolated,
+        land::{LandPlants, LAND_SIZE},
+        rng::GameRng,
         toolbar::{SunCount, SunCounter},
-        GridPos, PlayerTextureResources, FLYING_Z,
+        GridPos, PlayerTextureResources, FLOATING_Z, FLYING_Z,
     },
-    Dying, SCREEN_RESOLUTION,
+    Dying, GameState,
 };
-use bevy::log::info;
 use bevy::prelude::*;
 use core::time::Duration;
-use vleue_kinetoscope::{
-    AnimatedImage, AnimatedImageController, AnimatedImagePlugin, AnimationPlayed,
-};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
 
 #[derive(Component)]
 pub struct Sunflower {
-    produce_timer: Timer,
+    pub produce_timer: Timer,
+    /// Size of the suns it produces
+    pub sun: SunSize,
 }
 
 impl Sunflower {
-    pub fn create(
-        pos: impl Into<GridPos>,
-        commands: &mut Commands,
-        textures: Res<PlayerTextureResources>,
-    ) {
-        let pos: GridPos = pos.into();
-        commands.spawn((
-            PlayerCommon::new("Sunflower", 100.),
-            PlantCommon,
-            Sunflower {
-                produce_timer: Timer::new(Duration::from_secs(15), TimerMode::Repeating),
-            },
-            AnimatedImageController::play(textures.sunflower.clone()),
-            pos.round()
-                .to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y),
-        ));
+    pub fn new() -> Self {
+        Sunflower {
+            produce_timer: Timer::new(Duration::from_secs(15), TimerMode::Repeating),
+            sun: SunSize::Normal,
+        }
+    }
+}
+
+impl Default for Sunflower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pixels fallen per second by suns from the sky
+const SUN_FALL_SPEED: f32 = 60.;
+/// Seconds a sun takes to pop out of a sunflower
+const SUN_POP_SECONDS: f32 = 0.6;
+/// Pixels above its path at the top of a sun's pop
+const SUN_POP_HEIGHT: f32 = 40.;
+/// Seconds a sun waits to be collected before it's gone
+const SUN_LIFETIME: f32 = 8.;
+/// Seconds before expiring when a sun starts blinking
+const SUN_BLINK_SECONDS: f32 = 3.;
+/// Seconds a collected sun takes to reach the counter
+const SUN_COLLECT_SECONDS: f32 = 0.5;
+
+/// How many suns a sun is worth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SunSize {
+    Small,
+    Normal,
+    Large,
+}
+
+impl SunSize {
+    pub fn value(&self) -> i32 {
+        match self {
+            SunSize::Small => 25,
+            SunSize::Normal => 50,
+            SunSize::Large => 100,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        match self {
+            SunSize::Small => 0.6,
+            SunSize::Normal => 1.,
+            SunSize::Large => 1.3,
+        }
     }
 }
 
+pub enum SunState {
+    /// Falling from the sky, or popping out of a sunflower
+    Moving {
+        from: Vec2,
+        to: Vec2,
+        /// Pixels above the straight path at the middle of the move
+        arc: f32,
+        timer: Timer,
+    },
+    /// Waiting on the lawn to be collected, until it expires
+    Resting { lifetime: Timer },
+    /// Flying to the sun counter, credited when it gets there
+    Collected { from: Vec2, timer: Timer },
+}
+
 #[derive(Component)]
+#[require(Interpolated, StateScoped<GameState> = StateScoped(GameState::Running))]
 pub struct Sun {
-    move_up: bool,
+    pub size: SunSize,
+    pub state: SunState,
+}
+
+impl Sun {
+    /// A sun falling from `from` straight down to `to`
+    pub fn falling(size: SunSize, from: Vec2, to: Vec2) -> Self {
+        Sun {
+            size,
+            state: SunState::Moving {
+                from,
+                to,
+                arc: 0.,
+                timer: Timer::from_seconds(from.distance(to) / SUN_FALL_SPEED, TimerMode::Once),
+            },
+        }
+    }
+
+    /// A sun popping out of a sunflower at `from`, settling at `to`
+    pub fn popping(size: SunSize, from: Vec2, to: Vec2) -> Self {
+        Sun {
+            size,
+            state: SunState::Moving {
+                from,
+                to,
+                arc: SUN_POP_HEIGHT,
+                timer: Timer::from_seconds(SUN_POP_SECONDS, TimerMode::Once),
+            },
+        }
+    }
+
+    /// A sun already on the lawn, waiting for `elapsed` seconds
+    pub fn resting(size: SunSize, elapsed: f32) -> Self {
+        let mut lifetime = Timer::from_seconds(SUN_LIFETIME, TimerMode::Once);
+        lifetime.set_elapsed(Duration::from_secs_f32(elapsed));
+        Sun {
+            size,
+            state: SunState::Resting { lifetime },
+        }
+    }
+
+    /// Whether the sun can still be collected
+    pub fn collectable(&self) -> bool {
+        !matches!(self.state, SunState::Collected { .. })
+    }
+
+    /// Where the sun ends up if nobody collects it
+    pub fn resting_place(&self, transform: &Transform) -> Vec3 {
+        match &self.state {
+            SunState::Moving { to, .. } => to.extend(transform.translation.z),
+            _ => transform.translation,
+        }
+    }
+
+    /// Spawns a sun that is collected when clicked
+    pub fn spawn(
+        self,
+        commands: &mut Commands,
+        textures: &PlayerTextureResources,
+        transform: Transform,
+    ) -> Entity {
+        let scale = self.size.scale();
+        commands
+            .spawn((
+                self,
+                Sprite::from_image(textures.sun.clone()),
+                transform.with_scale(Vec3::splat(scale)),
+                Pickable::default(),
+            ))
+            .observe(sun_clicked)
+            .id()
+    }
+}
+
+/// Collect the targeted sun.
+///
+/// Clicking a sun triggers this, and it can also be
+/// triggered directly (e.g. in a headless simulation).
+/// The sun flies to the counter before it's credited.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollectSun;
+
+fn sun_clicked(trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger_targets(CollectSun, trigger.target());
+}
+
+pub fn collect_sun(
+    trigger: Trigger<CollectSun>,
+    mut suns: Query<(&mut Sun, &mut Transform, &mut Visibility), Without<Dying>>,
+) {
+    let Ok((mut sun, mut transform, mut visibility)) = suns.get_mut(trigger.target()) else {
+        return;
+    };
+    // Ignore suns that are already collected
+    if !sun.collectable() {
+        return;
+    }
+    sun.state = SunState::Collected {
+        from: transform.translation.xy(),
+        timer: Timer::from_seconds(SUN_COLLECT_SECONDS, TimerMode::Once),
+    };
+    // Over the toolbar, so it's seen reaching the counter
+    transform.translation.z = FLOATING_Z;
+    *visibility = Visibility::Inherited;
 }
 
 pub fn sunflow_gen_sun(
     mut commands: Commands,
     time: Res<Time>,
     textures: Res<PlayerTextureResources>,
+    mut rng: ResMut<GameRng>,
     sunflowers: Query<(&mut Sunflower, &Transform)>,
 ) {
     for (mut sunflower, pos) in sunflowers {
         sunflower.produce_timer.tick(time.delta());
         if sunflower.produce_timer.finished() {
-            commands
-                .spawn((
-                    Sun { move_up: true },
-                    Sprite::from_image(textures.sun.clone()),
-                    pos.clone(),
-                    Pickable::default(),
-                ))
-                .observe(
-                    |trigger: Trigger<Pointer<Click>>,
-                     mut commands: Commands,
-                     mut sun_count: ResMut<SunCount>| {
-                        // The sun is clicked, collect it
-                        commands.entity(trigger.target()).insert(Dying);
-                        sun_count.0 += 50;
-                    },
-                );
+            let from = pos.translation.xy();
+            let offset = Vec2::new(rng.f32() * 60. - 30., -20. - rng.f32() * 20.);
+            let mut transform = pos.clone();
+            transform.translation.z = FLYING_Z;
+            Sun::popping(sunflower.sun, from, from + offset).spawn(
+                &mut commands,
+                &textures,
+                transform,
+            );
         }
     }
 }
 
+/// Move suns through their lifecycle: moving, resting
+/// and blinking before expiring, or flying to the counter
 pub fn move_sun(
     time: Res<Time>,
     mut commands: Commands,
-    suns: Query<(Entity, &mut Transform, &Sun)>,
+    mut sun_count: ResMut<SunCount>,
+    counter: Single<&Transform, (With<SunCounter>, Without<Sun>)>,
+    suns: Query<(Entity, &mut Transform, &mut Sun, &mut Visibility)>,
 ) {
-    for (entity, mut sun_pos, dir) in suns {
-        let direction = match dir.move_up {
-            true => 1.,
-            false => -1.,
-        };
-        sun_pos.translation.y += time.delta().as_millis() as f32 / 100. * direction;
-
-        if sun_pos.translation.y >= SCREEN_RESOLUTION.y + 80. / 2. {
-            commands.entity(entity).insert(Dying);
+    for (entity, mut transform, mut sun, mut visibility) in suns {
+        let value = sun.size.value();
+        match &mut sun.state {
+            SunState::Moving {
+                from,
+                to,
+                arc,
+                timer,
+            } => {
+                timer.tick(time.delta());
+                let progress = timer.fraction();
+                let position = from.lerp(*to, progress) + Vec2::Y * (PI * progress).sin() * *arc;
+                transform.translation = position.extend(transform.translation.z);
+                if timer.finished() {
+                    transform.translation = to.extend(transform.translation.z);
+                    sun.state = SunState::Resting {
+                        lifetime: Timer::from_seconds(SUN_LIFETIME, TimerMode::Once),
+                    };
+                }
+            }
+            SunState::Resting { lifetime } => {
+                lifetime.tick(time.delta());
+                if lifetime.finished() {
+                    commands.entity(entity).insert(Dying);
+                    continue;
+                }
+                let remaining = lifetime.remaining_secs();
+                *visibility =
+                    match remaining < SUN_BLINK_SECONDS && (remaining * 4.) as u32 % 2 == 0 {
+                        true => Visibility::Hidden,
+                        false => Visibility::Inherited,
+                    };
+            }
+            SunState::Collected { from, timer } => {
+                timer.tick(time.delta());
+                let position = from.lerp(counter.translation.xy(), timer.fraction());
+                transform.translation = position.extend(transform.translation.z);
+                if timer.finished() {
+                    commands.entity(entity).insert(Dying);
+                    sun_count.0 += value;
+                }
+            }
         }
     }
 }
@@ -108,26 +292,22 @@ pub fn gen_sun_from_sky(
     mut gstimer: ResMut<GlobalSunTimer>,
     time: Res<Time>,
     textures: Res<PlayerTextureResources>,
+    mut rng: ResMut<GameRng>,
+    map: Res<LandPlants>,
 ) {
     gstimer.0.tick(time.delta());
 
     if gstimer.0.finished() {
-        let pos = GridPos::new(fastrand::f32() * LAND_SIZE.x, LAND_SIZE.y + 2.);
-        commands
-            .spawn((
-                Sun { move_up: false },
-                Sprite::from_image(textures.sun.clone()),
-                pos.to_world_transform(FLYING_Z),
-                Pickable::default(),
-            ))
-            .observe(
-                |trigger: Trigger<Pointer<Click>>,
-                 mut commands: Commands,
-                 mut sun_count: ResMut<SunCount>| {
-                    // The sun is clicked, collect it
-                    commands.entity(trigger.target()).insert(Dying);
-                    sun_count.0 += 50;
-                },
-            );
+        let (columns, rows) = map.size;
+        let x = rng.f32() * (columns - 1) as f32;
+        // Fall from above the screen, whatever the size of the lawn
+        let from = GridPos::new(x, LAND_SIZE.y + 2.);
+        // Land somewhere on the tiles of the level
+        let to = GridPos::new(x, rng.f32() * (rows - 1) as f32);
+        Sun::falling(SunSize::Normal, from.to_world(), to.to_world()).spawn(
+            &mut commands,
+            &textures,
+            from.to_world_transform(FLYING_Z),
+        );
     }
 }
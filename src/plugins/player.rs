@@ -1,8 +1,10 @@
 //! Defines common behaviors of players, including plants and zombies.
 
-use crate::Dying;
+use crate::{Dying, GameState};
 
+use super::headless::TextureLoader;
 use super::land::{GridPos, LandPlants, LAND_SIZE};
+use super::status::StatusEffects;
 use super::PLAYERS_Z;
 use bevy::ecs::component::HookContext;
 use bevy::ecs::world::DeferredWorld;
@@ -16,15 +18,57 @@ use web_time::Instant;
 
 /// Components common for plants and zombies
 #[derive(Debug, Clone, Component)]
-#[require(Transform)]
+#[require(
+    Transform,
+    StatusEffects,
+    StateScoped<GameState> = StateScoped(GameState::Running)
+)]
 pub struct PlayerCommon {
     pub name: &'static str,
     pub health: f32,
     pub spawned_time: Instant,
 }
 
-#[derive(Event)]
-pub struct DieEvent;
+/// What dealt some damage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    Bite,
+    Projectile,
+    Explosion,
+    Mower,
+    Burn,
+}
+
+/// Triggered on a player when it's hit, even when
+/// its armor takes the whole hit
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Damaged {
+    /// Health lost, after what armor absorbed
+    pub amount: f32,
+    /// Damage taken by the armor instead of health
+    pub absorbed: f32,
+    /// The plant, zombie or mower dealing the damage, if there's one
+    pub source: Option<Entity>,
+    pub kind: DamageKind,
+}
+
+impl Damaged {
+    pub fn new(amount: f32, kind: DamageKind, source: impl Into<Option<Entity>>) -> Self {
+        Damaged {
+            amount,
+            absorbed: 0.,
+            source: source.into(),
+            kind,
+        }
+    }
+}
+
+/// Triggered on a player when its health drops to 0
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Died {
+    pub cause: DamageKind,
+    pub killer: Option<Entity>,
+}
 
 impl PlayerCommon {
     pub fn new(name: &'static str, health: impl ToPrimitive) -> Self {
@@ -35,11 +79,20 @@ impl PlayerCommon {
         }
     }
 
-    pub fn damage(&mut self, commands: &mut Commands, amount: f32) {
-        self.health -= amount;
+    /// Damage the player `target`, this component
+    pub fn damage(&mut self, commands: &mut Commands, target: Entity, damage: Damaged) {
+        let was_alive = self.health > 0.;
+        self.health -= damage.amount;
+        commands.trigger_targets(damage, target);
 
-        if self.health <= 0. {
-            commands.trigger(DieEvent);
+        if was_alive && self.health <= 0. {
+            commands.trigger_targets(
+                Died {
+                    cause: damage.kind,
+                    killer: damage.source,
+                },
+                target,
+            );
         }
     }
 }
@@ -54,7 +107,8 @@ impl Default for PlayerCommon {
     }
 }
 
-/// A resource storing textures for plants and zombies
+/// A resource storing textures for zombies, and for what plants
+/// spawn. Plants themselves are in the [PlantRegistry](super::plants::registry::PlantRegistry).
 #[derive(Resource)]
 pub struct PlayerTextureResources {
     pub basic_zombie: Handle<AnimatedImage>,
@@ -62,38 +116,28 @@ pub struct PlayerTextureResources {
     pub eating_zombie: Handle<AnimatedImage>,
     pub eating_conehead_zombie: Handle<AnimatedImage>,
     pub jumping_zombie: Handle<AnimatedImage>,
-    pub sunflower: Handle<AnimatedImage>,
+    pub flag_zombie: Handle<Image>,
     pub sun: Handle<Image>,
-    pub shooter: Handle<AnimatedImage>,
     pub shooter_bullet: Handle<Image>,
-    pub wallnut: Handle<Image>,
 }
 
 impl PlayerTextureResources {
-    pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    pub fn setup(mut commands: Commands, asset_server: TextureLoader) {
         commands.insert_resource(PlayerTextureResources {
             basic_zombie: asset_server.load("zombie.gif"),
             conehead_zombie: asset_server.load("conehead_zombie_moving.gif"),
             eating_zombie: asset_server.load("eating_zombie.gif"),
             eating_conehead_zombie: asset_server.load("ConeheadZombieAttack.gif"),
             jumping_zombie: asset_server.load("jumping_zombie_walk.gif"),
-            sunflower: asset_server.load("SunFlower.gif"),
+            flag_zombie: asset_server.load("Flag_Zombie1.png"),
             sun: asset_server.load("Sun_transparent_background.png"),
-            shooter: asset_server.load("PeaShooter.gif"),
             shooter_bullet: asset_server.load("pea.png"),
-            wallnut: asset_server.load("Wall-nut1.png"),
         });
     }
 }
 
-pub fn dead_cleaner(
-    _trigger: Trigger<DieEvent>,
-    mut commands: Commands,
-    players: Query<(Entity, &mut PlayerCommon)>,
-) {
-    for (ent, pc) in players {
-        if pc.health <= 0. {
-            commands.entity(ent).insert(Dying);
-        }
-    }
+/// Remove dead players right away, so nothing targets them anymore.
+/// The [death](super::death) plugin leaves a corpse in their place.
+pub fn dead_cleaner(trigger: Trigger<Died>, mut commands: Commands) {
+    commands.entity(trigger.target()).insert(Dying);
 }
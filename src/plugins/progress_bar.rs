@@ -0,0 +1,90 @@
+//! The level progress bar under the sun counter.
+//!
+//! It fills as waves are sent and their zombies are killed,
+//! and shows a flag at every huge wave.
+
+use super::{
+    zombies::{
+        create_zombie::{self, WaveDirector},
+        ZombieCommon,
+    },
+    TOOLBAR_Z,
+};
+use crate::{GameState, PauseState};
+use bevy::{prelude::*, sprite::Anchor};
+
+pub struct ProgressBarPlugin;
+
+impl Plugin for ProgressBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Running),
+            setup.after(create_zombie::setup),
+        );
+        app.add_systems(
+            Update,
+            update_progress.run_if(in_state(PauseState::Playing)),
+        );
+    }
+}
+
+const BAR_SIZE: Vec2 = Vec2::new(150., 14.);
+/// Center of the bar, right under the sun counter
+const BAR_CENTER: Vec2 = Vec2::new(-400. + 163. / 2., 300. - 48. - 12.);
+
+/// Marker component for the filled part of the bar
+#[derive(Component)]
+struct ProgressFill;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, director: Res<WaveDirector>) {
+    let image: Handle<Image> = asset_server.load("Bar.png");
+    let left = BAR_CENTER.x - BAR_SIZE.x / 2.;
+
+    // The empty track
+    commands.spawn((
+        StateScoped(GameState::Running),
+        Sprite {
+            image: image.clone(),
+            color: Color::srgb(0.3, 0.3, 0.3),
+            custom_size: Some(BAR_SIZE),
+            ..default()
+        },
+        Transform::from_xyz(BAR_CENTER.x, BAR_CENTER.y, TOOLBAR_Z),
+    ));
+
+    commands.spawn((
+        ProgressFill,
+        StateScoped(GameState::Running),
+        Sprite {
+            image,
+            custom_size: Some(Vec2::new(0., BAR_SIZE.y)),
+            anchor: Anchor::CenterLeft,
+            ..default()
+        },
+        Transform::from_xyz(left, BAR_CENTER.y, TOOLBAR_Z + 0.1),
+    ));
+
+    // Flags at huge waves
+    for wave in (1..=director.total_waves).filter(|wave| director.is_huge_wave(*wave)) {
+        let x = left + BAR_SIZE.x * wave as f32 / director.total_waves as f32;
+        commands
+            .spawn((
+                StateScoped(GameState::Running),
+                Sprite::from_color(Color::WHITE, Vec2::new(2., BAR_SIZE.y + 6.)),
+                Transform::from_xyz(x, BAR_CENTER.y, TOOLBAR_Z + 0.2),
+            ))
+            .with_child((
+                Sprite::from_color(Color::srgb(0.8, 0., 0.), Vec2::new(6., 5.)),
+                Transform::from_xyz(-4., BAR_SIZE.y / 2., 0.),
+            ));
+    }
+}
+
+fn update_progress(
+    director: Res<WaveDirector>,
+    zombies: Query<(), With<ZombieCommon>>,
+    mut fill: Single<&mut Sprite, With<ProgressFill>>,
+) {
+    let progress = director.progress(zombies.iter().count());
+    fill.custom_size = Some(Vec2::new(BAR_SIZE.x * progress, BAR_SIZE.y));
+}
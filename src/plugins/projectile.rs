@@ -0,0 +1,192 @@
+//! Everything shot at zombies.
+//!
+//! A shooter only spawns a [Projectile] with how it flies and what it
+//! does on hit, moving it and hitting zombies is done here for all of
+//! them. Straight, backwards or diagonal shots are a matter of
+//! velocity, lobbed ones also have gravity.
+
+use super::{
+    interpolation::Interpolated,
+    player::{DamageKind, Damaged, PlayerCommon},
+    zombies::{
+        armor::{damage_with_armor, Armor},
+        LandZombies, ZombieCommon,
+    },
+    GridPos,
+};
+use crate::{Dying, GameState, PauseState, SCREEN_RESOLUTION};
+use bevy::prelude::*;
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            move_projectiles.run_if(in_state(PauseState::Playing)),
+        );
+    }
+}
+
+/// Where a zombie is, as far as projectiles are concerned
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HitLayer {
+    #[default]
+    Ground,
+    /// Jumping or flying
+    Air,
+    /// Behind something that stops projectiles
+    Shielded,
+}
+
+/// Which [HitLayer]s a projectile can hit
+#[derive(Debug, Clone, Copy)]
+pub struct HitFilter {
+    pub ground: bool,
+    pub air: bool,
+    pub shielded: bool,
+}
+
+impl HitFilter {
+    pub const GROUND: HitFilter = HitFilter {
+        ground: true,
+        air: false,
+        shielded: false,
+    };
+
+    pub fn allows(&self, layer: HitLayer) -> bool {
+        match layer {
+            HitLayer::Ground => self.ground,
+            HitLayer::Air => self.air,
+            HitLayer::Shielded => self.shielded,
+        }
+    }
+}
+
+/// Something flying that damages the zombies it hits
+#[derive(Component)]
+#[require(
+    ProjectileHits,
+    Interpolated,
+    StateScoped<GameState> = StateScoped(GameState::Running)
+)]
+pub struct Projectile {
+    /// Pixels flown per second
+    pub velocity: Vec2,
+    /// Pixels per second² pulling it down, 0 to fly straight
+    pub gravity: f32,
+    pub damage: f32,
+    /// Pixels from a zombie's center at which it hits
+    pub radius: f32,
+    /// How many zombies it goes through before it's used up
+    pub pierce: u32,
+    pub hits: HitFilter,
+    /// Vanishes when finished, otherwise only when off screen
+    pub lifetime: Option<Timer>,
+    /// Done to every zombie hit, on top of the damage
+    pub on_hit: Vec<fn(&mut EntityCommands)>,
+    /// Who shot it, credited for the damage
+    pub shooter: Option<Entity>,
+}
+
+/// Zombies already hit by a projectile, so piercing doesn't hit them twice
+#[derive(Component, Default)]
+pub struct ProjectileHits(Vec<Entity>);
+
+impl Projectile {
+    /// A projectile flying straight, hitting the first zombie
+    /// on the ground. Change the fields for anything else.
+    pub fn new(velocity: Vec2, damage: f32) -> Self {
+        Projectile {
+            velocity,
+            gravity: 0.,
+            damage,
+            radius: 10.,
+            pierce: 0,
+            hits: HitFilter::GROUND,
+            lifetime: None,
+            on_hit: Vec::new(),
+            shooter: None,
+        }
+    }
+}
+
+/// Move projectiles, and hit the zombies they reach
+pub fn move_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    projectiles: Query<(Entity, &mut Projectile, &mut ProjectileHits, &mut Transform)>,
+    zombies: Query<(&Transform, &HitLayer), (With<ZombieCommon>, Without<Projectile>)>,
+    mut zombie_health: Query<(&mut PlayerCommon, Option<&mut Armor>), With<ZombieCommon>>,
+    land_zombies: Res<LandZombies>,
+) {
+    'projectiles: for (entity, mut projectile, mut hits, mut transform) in projectiles {
+        if let Some(lifetime) = &mut projectile.lifetime {
+            lifetime.tick(time.delta());
+            if lifetime.finished() {
+                commands.entity(entity).insert(Dying);
+                continue;
+            }
+        }
+
+        let delta = time.delta_secs();
+        transform.translation += (projectile.velocity * delta).extend(0.);
+        projectile.velocity.y -= projectile.gravity * delta;
+
+        // Check whether it's out of screen
+        let margin = 28. / 2.;
+        if transform.translation.x.abs() >= SCREEN_RESOLUTION.x + margin
+            || transform.translation.y.abs() >= SCREEN_RESOLUTION.y + margin
+        {
+            commands.entity(entity).insert(Dying);
+            continue;
+        }
+
+        // Check whether it has collided with zombies on its row
+        let grid_pos: GridPos = GridPos::from(*transform).round();
+        if grid_pos.y < 0. {
+            continue;
+        }
+        let Some(row_zombies) = land_zombies.rows.get(grid_pos.y as usize) else {
+            continue;
+        };
+        for zombie in row_zombies {
+            let Ok((zombie_pos, layer)) = zombies.get(*zombie) else {
+                continue;
+            };
+            if !projectile.hits.allows(*layer)
+                || hits.0.contains(zombie)
+                || (transform.translation - zombie_pos.translation).length() >= projectile.radius
+            {
+                continue;
+            }
+
+            let (mut health, armor) = zombie_health.get_mut(*zombie).unwrap();
+            if health.health <= 0. {
+                // Killed earlier this tick, it's only waiting to be removed
+                continue;
+            }
+            damage_with_armor(
+                &mut commands,
+                *zombie,
+                &mut health,
+                armor,
+                Damaged::new(
+                    projectile.damage,
+                    DamageKind::Projectile,
+                    projectile.shooter,
+                ),
+            );
+            for effect in &projectile.on_hit {
+                effect(&mut commands.entity(*zombie));
+            }
+
+            if projectile.pierce == 0 {
+                commands.entity(entity).insert(Dying);
+                continue 'projectiles;
+            }
+            projectile.pierce -= 1;
+            hits.0.push(*zombie);
+        }
+    }
+}
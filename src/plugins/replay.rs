@@ -0,0 +1,440 @@
+//! Recording the player's actions during a level, and playing them back.
+//!
+//! Run with `--record <file>` to write a replay when the level ends, and
+//! with `--replay <file>` to play it back, with or without `--headless`.
+//!
+//! A replay holds the seed and the actions stamped with the gameplay
+//! tick they happened before, which is all it takes to play the same
+//! game again, at any frame rate. A hash of the world is also stored
+//! every [CHECK_INTERVAL] ticks, so a playback that goes differently
+//! is reported at the tick it happens.
+
+use super::{
+    level::{CurrentLevel, LoadedLevel},
+    plants::{sunflower::CollectSun, sunflower::Sun, PlantKind},
+    player::PlayerCommon,
+    rng::{seed_rng, GameRng, SeedArg},
+    save::PendingSave,
+    toolbar::{DigAction, PlantAction, SunCount},
+    zombies::create_zombie::ZombieType,
+};
+use crate::{Dying, GameState, PauseState};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+};
+use thiserror::Error;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimTick>();
+        app.add_systems(OnEnter(GameState::Running), reset_tick);
+        app.add_systems(FixedLast, count_tick.run_if(in_state(PauseState::Playing)));
+
+        if let Some(path) = path_from_args("--record") {
+            app.insert_resource(RecordTo(path));
+            app.add_systems(OnEnter(GameState::Running), start_recording.after(seed_rng));
+            app.add_systems(OnExit(GameState::Running), write_recording);
+            app.add_systems(
+                FixedLast,
+                record_check
+                    .after(count_tick)
+                    .run_if(in_state(PauseState::Playing).and(resource_exists::<Recording>)),
+            );
+            app.add_observer(record_plant);
+            app.add_observer(record_dig);
+            app.add_observer(record_collect_sun);
+        }
+
+        if let Some(path) = path_from_args("--replay") {
+            match Replay::read(&path) {
+                Ok(replay) => {
+                    app.insert_resource(SeedArg(Some(replay.seed)));
+                    app.insert_resource(Playback::new(replay));
+                    app.add_systems(
+                        Update,
+                        start_playback.run_if(in_state(GameState::WaitForStart)),
+                    );
+                    app.add_systems(
+                        FixedPreUpdate,
+                        play_actions.run_if(in_state(PauseState::Playing)),
+                    );
+                    app.add_systems(
+                        FixedLast,
+                        check_playback
+                            .after(count_tick)
+                            .run_if(in_state(PauseState::Playing)),
+                    );
+                    app.add_systems(OnExit(GameState::Running), report_playback);
+                }
+                Err(err) => error!("Can't play replay {}: {err}", path.display()),
+            }
+        }
+    }
+}
+
+/// A hash of the world is stored every this many ticks
+pub const CHECK_INTERVAL: u64 = 60;
+/// Bumped whenever the replay format changes
+const REPLAY_VERSION: u32 = 2;
+
+/// The **Resource** counting the gameplay ticks of the level
+#[derive(Resource, Default)]
+pub struct SimTick(pub u64);
+
+fn reset_tick(mut tick: ResMut<SimTick>) {
+    tick.0 = 0;
+}
+
+fn count_tick(mut tick: ResMut<SimTick>) {
+    tick.0 += 1;
+}
+
+/// Read `<flag> <path>` from the command line
+fn path_from_args(flag: &str) -> Option<PathBuf> {
+    let mut args = std::env::args();
+    args.find(|arg| arg == flag)?;
+    args.next().map(PathBuf::from)
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("could not access replay: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse replay: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write replay: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("replay is version {0}, but only version {REPLAY_VERSION} is supported")]
+    Version(u32),
+}
+
+/// Something the player did
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ReplayAction {
+    Plant(PlantAction),
+    Dig(DigAction),
+    /// Suns are found by where they are, as entities
+    /// are different each time the game runs
+    CollectSun {
+        position: (f32, f32),
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TimedAction {
+    tick: u64,
+    action: ReplayAction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorldCheck {
+    tick: u64,
+    hash: u64,
+}
+
+/// Everything needed to play a level again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    /// Asset path of the level
+    level: String,
+    seed: u64,
+    /// How many ticks the level lasted
+    ticks: u64,
+    actions: Vec<TimedAction>,
+    checks: Vec<WorldCheck>,
+}
+
+impl Replay {
+    pub fn read(path: &PathBuf) -> Result<Replay, ReplayError> {
+        let text = std::fs::read_to_string(path)?;
+        let replay: Replay = ron::from_str(&text)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(replay.version));
+        }
+        Ok(replay)
+    }
+
+    fn write(&self, path: &PathBuf) -> Result<(), ReplayError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// Hash of what matters to the game, to find out when a playback goes
+/// differently than the recording. Entities are sorted, as they may
+/// be iterated in another order.
+fn world_hash(
+    sun_count: &SunCount,
+    rng: &GameRng,
+    plants: &Query<(&PlantKind, &Transform, &PlayerCommon), Without<Dying>>,
+    zombies: &Query<(&ZombieType, &Transform, &PlayerCommon), Without<Dying>>,
+) -> u64 {
+    let mut plants: Vec<_> = plants
+        .iter()
+        .map(|(kind, transform, common)| {
+            (
+                *kind as u8,
+                transform.translation.x.to_bits(),
+                transform.translation.y.to_bits(),
+                common.health.to_bits(),
+            )
+        })
+        .collect();
+    plants.sort();
+    let mut zombies: Vec<_> = zombies
+        .iter()
+        .map(|(kind, transform, common)| {
+            (
+                *kind as u8,
+                transform.translation.x.to_bits(),
+                transform.translation.y.to_bits(),
+                common.health.to_bits(),
+            )
+        })
+        .collect();
+    zombies.sort();
+
+    let mut hasher = DefaultHasher::new();
+    sun_count.0.hash(&mut hasher);
+    rng.get_seed().hash(&mut hasher);
+    plants.hash(&mut hasher);
+    zombies.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The **Resource** holding where to write the recording
+#[derive(Resource)]
+struct RecordTo(PathBuf);
+
+/// The **Resource** holding the replay being recorded
+#[derive(Resource)]
+struct Recording(Replay);
+
+fn start_recording(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    rng: Res<GameRng>,
+    pending_save: Option<Res<PendingSave>>,
+) {
+    if pending_save.is_some() {
+        warn!("A resumed game can't be recorded, a replay always starts with the level");
+        return;
+    }
+    commands.insert_resource(Recording(Replay {
+        version: REPLAY_VERSION,
+        level: current_level
+            .0
+            .path()
+            .map(|path| path.to_string())
+            .unwrap_or_default(),
+        seed: rng.seed(),
+        ticks: 0,
+        actions: Vec::new(),
+        checks: Vec::new(),
+    }));
+}
+
+fn record_check(
+    tick: Res<SimTick>,
+    mut recording: ResMut<Recording>,
+    sun_count: Res<SunCount>,
+    rng: Res<GameRng>,
+    plants: Query<(&PlantKind, &Transform, &PlayerCommon), Without<Dying>>,
+    zombies: Query<(&ZombieType, &Transform, &PlayerCommon), Without<Dying>>,
+) {
+    if tick.0 % CHECK_INTERVAL == 0 {
+        recording.0.checks.push(WorldCheck {
+            tick: tick.0,
+            hash: world_hash(&sun_count, &rng, &plants, &zombies),
+        });
+    }
+}
+
+fn record_action(recording: Option<ResMut<Recording>>, tick: &SimTick, action: ReplayAction) {
+    if let Some(mut recording) = recording {
+        recording.0.actions.push(TimedAction {
+            tick: tick.0,
+            action,
+        });
+    }
+}
+
+fn record_plant(
+    trigger: Trigger<PlantAction>,
+    recording: Option<ResMut<Recording>>,
+    tick: Res<SimTick>,
+) {
+    record_action(recording, &tick, ReplayAction::Plant(*trigger.event()));
+}
+
+fn record_dig(
+    trigger: Trigger<DigAction>,
+    recording: Option<ResMut<Recording>>,
+    tick: Res<SimTick>,
+) {
+    record_action(recording, &tick, ReplayAction::Dig(*trigger.event()));
+}
+
+fn record_collect_sun(
+    trigger: Trigger<CollectSun>,
+    recording: Option<ResMut<Recording>>,
+    tick: Res<SimTick>,
+    suns: Query<&Transform, (With<Sun>, Without<Dying>)>,
+) {
+    let Ok(transform) = suns.get(trigger.target()) else {
+        return;
+    };
+    let position = (transform.translation.x, transform.translation.y);
+    record_action(recording, &tick, ReplayAction::CollectSun { position });
+}
+
+fn write_recording(
+    mut commands: Commands,
+    recording: Option<ResMut<Recording>>,
+    record_to: Res<RecordTo>,
+    tick: Res<SimTick>,
+) {
+    let Some(mut recording) = recording else {
+        return;
+    };
+    recording.0.ticks = tick.0;
+    match recording.0.write(&record_to.0) {
+        Ok(()) => info!("Replay saved to {}", record_to.0.display()),
+        Err(err) => error!("Failed to save the replay: {err}"),
+    }
+    commands.remove_resource::<Recording>();
+}
+
+/// The **Resource** holding the replay being played back
+#[derive(Resource)]
+struct Playback {
+    replay: Replay,
+    /// Index of the next action to play
+    next_action: usize,
+    /// Tick of the first desync, if any
+    desynced_at: Option<u64>,
+}
+
+impl Playback {
+    fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            next_action: 0,
+            desynced_at: None,
+        }
+    }
+
+    fn desync(&mut self, tick: u64, reason: &str) {
+        if self.desynced_at.is_none() {
+            error!("Replay desynced at tick {tick}: {reason}");
+            self.desynced_at = Some(tick);
+        }
+    }
+}
+
+/// Start the level right away, without the start screen
+fn start_playback(
+    mut commands: Commands,
+    level: LoadedLevel,
+    current_level: Res<CurrentLevel>,
+    playback: Res<Playback>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !level.is_loaded() {
+        return;
+    }
+    let path = current_level.0.path().map(|path| path.to_string());
+    if path.as_deref() != Some(playback.replay.level.as_str()) {
+        error!(
+            "The replay is for level {}, but {path:?} is being played",
+            playback.replay.level
+        );
+        commands.remove_resource::<Playback>();
+        return;
+    }
+    next_state.set(GameState::Running);
+}
+
+fn play_actions(
+    mut commands: Commands,
+    tick: Res<SimTick>,
+    playback: Option<ResMut<Playback>>,
+    suns: Query<(Entity, &Transform), (With<Sun>, Without<Dying>)>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    while let Some(timed) = playback.replay.actions.get(playback.next_action) {
+        if timed.tick != tick.0 {
+            break;
+        }
+        let action = timed.action;
+        playback.next_action += 1;
+        match action {
+            ReplayAction::Plant(plant) => commands.trigger(plant),
+            ReplayAction::Dig(dig) => commands.trigger(dig),
+            ReplayAction::CollectSun { position } => {
+                let position = Vec2::new(position.0, position.1);
+                let sun = suns
+                    .iter()
+                    .map(|(sun, transform)| (sun, transform.translation.xy().distance(position)))
+                    .filter(|(_, distance)| *distance < 1.)
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                match sun {
+                    Some((sun, _)) => commands.trigger_targets(CollectSun, sun),
+                    None => playback.desync(tick.0, "the collected sun isn't there"),
+                }
+            }
+        }
+    }
+}
+
+fn check_playback(
+    tick: Res<SimTick>,
+    playback: Option<ResMut<Playback>>,
+    sun_count: Res<SunCount>,
+    rng: Res<GameRng>,
+    plants: Query<(&PlantKind, &Transform, &PlayerCommon), Without<Dying>>,
+    zombies: Query<(&ZombieType, &Transform, &PlayerCommon), Without<Dying>>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if tick.0 % CHECK_INTERVAL != 0 {
+        return;
+    }
+    let recorded = playback
+        .replay
+        .checks
+        .iter()
+        .find(|check| check.tick == tick.0)
+        .map(|check| check.hash);
+    if let Some(recorded) = recorded {
+        if recorded != world_hash(&sun_count, &rng, &plants, &zombies) {
+            playback.desync(tick.0, "the world is different than when recorded");
+        }
+    }
+}
+
+fn report_playback(mut commands: Commands, tick: Res<SimTick>, playback: Option<Res<Playback>>) {
+    let Some(playback) = playback else {
+        return;
+    };
+    let recorded = playback.replay.ticks;
+    if playback.desynced_at.is_none() && tick.0 == recorded {
+        info!("Replay played back without desync");
+    } else if playback.desynced_at.is_none() {
+        error!(
+            "Replay ended at tick {}, but {recorded} ticks were recorded",
+            tick.0
+        );
+    }
+    commands.remove_resource::<Playback>();
+}
@@ -0,0 +1,87 @@
+//! The random number generator behind every random decision in the game.
+//!
+//! It's seeded when a level starts: from `--seed <n>` on the command
+//! line if given, else from the level file, else randomly. Playing a
+//! level again with the same seed makes the same decisions.
+
+use super::level::LoadedLevel;
+use crate::GameState;
+use bevy::prelude::*;
+use fastrand::Rng;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SeedArg(seed_from_args()));
+        app.add_systems(OnEnter(GameState::Running), seed_rng);
+    }
+}
+
+/// The **Resource** holding the seed given on the command line
+#[derive(Resource)]
+pub struct SeedArg(pub Option<u64>);
+
+/// The **Resource** to get random numbers from.
+///
+/// Never use the global `fastrand` functions in the game,
+/// or the game can't be reproduced from its seed.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: Rng::with_seed(seed),
+        }
+    }
+
+    /// Continue from a state returned by [Rng::get_seed],
+    /// e.g. when resuming a saved game
+    pub fn resume(seed: u64, state: u64) -> Self {
+        GameRng {
+            seed,
+            rng: Rng::with_seed(state),
+        }
+    }
+
+    /// The seed this level was started with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+/// Read `--seed <n>` or `--seed=<n>`
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args();
+    let value = loop {
+        let arg = args.next()?;
+        if arg == "--seed" {
+            break args.next()?;
+        }
+        if let Some(value) = arg.strip_prefix("--seed=") {
+            break value.to_string();
+        }
+    };
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(err) => {
+            warn!("Ignoring invalid seed {value:?}: {err}");
+            None
+        }
+    }
+}
+
+pub fn seed_rng(mut commands: Commands, arg: Res<SeedArg>, level: LoadedLevel) {
+    let seed = arg
+        .0
+        .or(level.get().seed)
+        .unwrap_or_else(|| fastrand::u64(..));
+    info!("Playing with seed {seed}");
+    commands.insert_resource(GameRng::new(seed));
+}
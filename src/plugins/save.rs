@@ -0,0 +1,512 @@
+//! Saving a running level to a file, and resuming it later.
+//!
+//! The save is written when [SaveEvent] is triggered from the pause menu.
+//! To resume, insert a [PendingSave] and enter the running state. The
+//! level is set up as usual, then the saved plants and zombies are
+//! spawned through their `create` functions, so that `LandPlants` and
+//! `LandZombies` are rebuilt by the hooks.
+
+use super::{
+    interpolation::Interpolated,
+    land::GridPos,
+    lawnmower::Lawnmower,
+    level::{CurrentLevel, LevelTime},
+    plants::{
+        cherrybomb::CherryBomb,
+        peashooter::{Bullet, Peashooter},
+        registry::PlantRegistry,
+        sunflower::{GlobalSunTimer, Sun, SunSize, SunState, Sunflower},
+        PlantKind, PlantPrice,
+    },
+    player::PlayerCommon,
+    rng::GameRng,
+    status::{StatusEffect, StatusEffects, StatusKind},
+    toolbar::{SunCount, ToolbarPlant},
+    zombies::{
+        armor::Armor,
+        create_zombie::{WaveDirector, ZombieCreateTimer, ZombieType},
+        jumping_zombie::{JumpingZombie, VaultState, Vaulting},
+        registry::ZombieRegistry,
+        ZombieCommon, ZombieState, ZombieStats,
+    },
+    PlayerTextureResources,
+};
+use crate::{Dying, PauseState};
+use bevy::prelude::*;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(save_game);
+        // Entering Playing happens right after all the OnEnter(Running)
+        // setup systems are applied, so the level is ready to be restored.
+        app.add_systems(
+            OnEnter(PauseState::Playing),
+            restore_save.run_if(resource_exists::<PendingSave>),
+        );
+    }
+}
+
+/// Where the game is saved, relative to the working directory
+const SAVE_PATH: &str = "savegame.ron";
+/// Bumped whenever the save format changes
+const SAVE_VERSION: u32 = 6;
+
+/// Trigger this to save the running level
+#[derive(Event)]
+pub struct SaveEvent;
+
+/// The **Resource** holding a save to be restored when the level starts
+#[derive(Resource)]
+pub struct PendingSave(pub SaveGame);
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("could not access {SAVE_PATH}: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse {SAVE_PATH}: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write save: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("save is version {0}, but only version {SAVE_VERSION} is supported")]
+    Version(u32),
+}
+
+/// A [Timer], without its mode
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct SavedTimer {
+    duration: f32,
+    elapsed: f32,
+}
+
+impl From<&Timer> for SavedTimer {
+    fn from(timer: &Timer) -> Self {
+        SavedTimer {
+            duration: timer.duration().as_secs_f32(),
+            elapsed: timer.elapsed_secs(),
+        }
+    }
+}
+
+impl SavedTimer {
+    fn apply(&self, timer: &mut Timer) {
+        timer.set_duration(Duration::from_secs_f32(self.duration));
+        timer.set_elapsed(Duration::from_secs_f32(self.elapsed));
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct SavedStatus {
+    kind: StatusKind,
+    timer: SavedTimer,
+}
+
+impl SavedStatus {
+    fn save_all(effects: &StatusEffects) -> Vec<SavedStatus> {
+        effects
+            .iter()
+            .map(|effect| SavedStatus {
+                kind: effect.kind,
+                timer: (&effect.timer).into(),
+            })
+            .collect()
+    }
+
+    /// Put the saved effects back on a player
+    fn restore_all(saved: &[SavedStatus], entity: &mut EntityCommands) {
+        for status in saved {
+            let mut effect = StatusEffect::new(status.kind, status.timer.duration);
+            status.timer.apply(&mut effect.timer);
+            effect.apply_to(entity);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedCard {
+    kind: PlantKind,
+    cooldown: SavedTimer,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPlant {
+    kind: PlantKind,
+    tile: (i32, i32),
+    health: f32,
+    price: Option<i32>,
+    /// Shoot, produce or fuse timer, depending on the plant
+    timer: Option<SavedTimer>,
+    effects: Vec<SavedStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedZombie {
+    kind: ZombieType,
+    position: (f32, f32),
+    health: f32,
+    /// Health of the armor, if it still wears one
+    armor: Option<f32>,
+    /// Jumping zombies that lost their pole
+    #[serde(default)]
+    vaulted: bool,
+    eating: bool,
+    /// Time until the next bite, while eating
+    bite_timer: SavedTimer,
+    effects: Vec<SavedStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedMower {
+    row: usize,
+    x: f32,
+    driving: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedSun {
+    /// Where it rests, suns still moving are saved where they land
+    position: (f32, f32, f32),
+    size: SunSize,
+    /// Seconds it has been waiting to be collected
+    resting: f32,
+}
+
+/// Everything needed to resume a level
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+    /// Asset path of the level
+    level: String,
+    level_time: f32,
+    /// Seed the level was started with
+    seed: u64,
+    /// Where the random numbers continue from
+    rng_state: u64,
+    suns: i32,
+    sky_sun_timer: SavedTimer,
+    zombie_timer: SavedTimer,
+    wave: u32,
+    huge_wave_warned: bool,
+    last_wave_size: u32,
+    cards: Vec<SavedCard>,
+    plants: Vec<SavedPlant>,
+    zombies: Vec<SavedZombie>,
+    /// Mowers that haven't been used up
+    mowers: Vec<SavedMower>,
+    suns_on_lawn: Vec<SavedSun>,
+    bullets: Vec<(f32, f32, f32)>,
+}
+
+impl SaveGame {
+    /// Is there a save to resume?
+    pub fn exists() -> bool {
+        std::path::Path::new(SAVE_PATH).exists()
+    }
+
+    pub fn read() -> Result<SaveGame, SaveError> {
+        let text = std::fs::read_to_string(SAVE_PATH)?;
+        let save: SaveGame = ron::from_str(&text)?;
+        if save.version != SAVE_VERSION {
+            return Err(SaveError::Version(save.version));
+        }
+        Ok(save)
+    }
+
+    fn write(&self) -> Result<(), SaveError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(SAVE_PATH, text)?;
+        Ok(())
+    }
+}
+
+fn save_game(
+    _trigger: Trigger<SaveEvent>,
+    current_level: Res<CurrentLevel>,
+    level_time: Res<LevelTime>,
+    rng: Res<GameRng>,
+    sun_count: Res<SunCount>,
+    sky_sun_timer: Res<GlobalSunTimer>,
+    zombie_timer: Res<ZombieCreateTimer>,
+    director: Res<WaveDirector>,
+    cards: Query<&ToolbarPlant>,
+    plants: Query<
+        (
+            &PlantKind,
+            &Transform,
+            &PlayerCommon,
+            &StatusEffects,
+            Option<&PlantPrice>,
+            Option<&Peashooter>,
+            Option<&Sunflower>,
+            Option<&CherryBomb>,
+        ),
+        Without<Dying>,
+    >,
+    zombies: Query<
+        (
+            &ZombieType,
+            &Transform,
+            &PlayerCommon,
+            &ZombieCommon,
+            &ZombieStats,
+            &StatusEffects,
+            Option<&Armor>,
+            Option<&JumpingZombie>,
+            Option<&Vaulting>,
+        ),
+        Without<Dying>,
+    >,
+    mowers: Query<(&Lawnmower, &Transform), Without<Dying>>,
+    suns: Query<(&Sun, &Transform), Without<Dying>>,
+    bullets: Query<&Transform, (With<Bullet>, Without<Dying>)>,
+) {
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        level: current_level
+            .0
+            .path()
+            .map(|path| path.to_string())
+            .unwrap_or_default(),
+        level_time: level_time.0.as_secs_f32(),
+        seed: rng.seed(),
+        rng_state: rng.get_seed(),
+        // Suns flying to the counter are as good as collected
+        suns: sun_count.0
+            + suns
+                .iter()
+                .filter(|(sun, _)| !sun.collectable())
+                .map(|(sun, _)| sun.size.value())
+                .sum::<i32>(),
+        sky_sun_timer: (&sky_sun_timer.0).into(),
+        zombie_timer: (&zombie_timer.timer).into(),
+        wave: director.wave,
+        huge_wave_warned: director.huge_wave_warned,
+        last_wave_size: director.last_wave_size,
+        cards: cards
+            .iter()
+            .map(|card| SavedCard {
+                kind: card.kind,
+                cooldown: (&card.cooldown).into(),
+            })
+            .collect(),
+        plants: plants
+            .iter()
+            .map(
+                |(kind, transform, common, effects, price, shooter, sunflower, bomb)| SavedPlant {
+                    kind: *kind,
+                    tile: GridPos::from(*transform).into(),
+                    health: common.health,
+                    price: price.map(|price| price.0),
+                    timer: shooter
+                        .map(|shooter| &shooter.shoot_timer)
+                        .or(sunflower.map(|sunflower| &sunflower.produce_timer))
+                        .or(bomb.map(|bomb| &bomb.fuse_timer))
+                        .map(SavedTimer::from),
+                    effects: SavedStatus::save_all(effects),
+                },
+            )
+            .collect(),
+        zombies: zombies
+            .iter()
+            .map(
+                |(kind, transform, common, zombie, stats, effects, armor, jumping, vaulting)| {
+                    SavedZombie {
+                        kind: *kind,
+                        // Zombies in the air are saved where they land
+                        position: vaulting
+                            .map(|vaulting| vaulting.landing())
+                            .unwrap_or(transform.translation.xy())
+                            .into(),
+                        health: common.health,
+                        armor: armor.map(|armor| armor.health),
+                        vaulted: jumping
+                            .is_some_and(|jumping| jumping.state != VaultState::Running),
+                        eating: zombie.state == ZombieState::Eating,
+                        bite_timer: (&stats.bite_timer).into(),
+                        effects: SavedStatus::save_all(effects),
+                    }
+                },
+            )
+            .collect(),
+        mowers: mowers
+            .iter()
+            .map(|(mower, transform)| SavedMower {
+                row: mower.row,
+                x: transform.translation.x,
+                driving: mower.driving,
+            })
+            .collect(),
+        suns_on_lawn: suns
+            .iter()
+            .filter(|(sun, _)| sun.collectable())
+            .map(|(sun, transform)| SavedSun {
+                position: sun.resting_place(transform).into(),
+                size: sun.size,
+                resting: match &sun.state {
+                    SunState::Resting { lifetime } => lifetime.elapsed_secs(),
+                    _ => 0.,
+                },
+            })
+            .collect(),
+        bullets: bullets
+            .iter()
+            .map(|transform| transform.translation.into())
+            .collect(),
+    };
+
+    match save.write() {
+        Ok(()) => info!("Game saved to {SAVE_PATH}"),
+        Err(err) => error!("Failed to save the game: {err}"),
+    }
+}
+
+fn restore_save(
+    mut commands: Commands,
+    save: Res<PendingSave>,
+    current_level: Res<CurrentLevel>,
+    textures: Res<PlayerTextureResources>,
+    registry: Res<PlantRegistry>,
+    zombie_registry: Res<ZombieRegistry>,
+    mut level_time: ResMut<LevelTime>,
+    mut sun_count: ResMut<SunCount>,
+    mut sky_sun_timer: ResMut<GlobalSunTimer>,
+    mut zombie_timer: ResMut<ZombieCreateTimer>,
+    mut director: ResMut<WaveDirector>,
+    cards: Query<&mut ToolbarPlant>,
+    mowers: Query<(Entity, &mut Lawnmower, &mut Transform)>,
+) {
+    commands.remove_resource::<PendingSave>();
+    let save = &save.0;
+
+    let level = current_level.0.path().map(|path| path.to_string());
+    if level.as_deref() != Some(save.level.as_str()) {
+        error!(
+            "The save is for level {}, but {level:?} is being played",
+            save.level
+        );
+        return;
+    }
+
+    level_time.0 = Duration::from_secs_f32(save.level_time);
+    commands.insert_resource(GameRng::resume(save.seed, save.rng_state));
+    sun_count.0 = save.suns;
+    save.sky_sun_timer.apply(&mut sky_sun_timer.0);
+    save.zombie_timer.apply(&mut zombie_timer.timer);
+    director.wave = save.wave;
+    director.huge_wave_warned = save.huge_wave_warned;
+    director.last_wave_size = save.last_wave_size;
+
+    for mut card in cards {
+        if let Some(saved) = save.cards.iter().find(|saved| saved.kind == card.kind) {
+            saved.cooldown.apply(&mut card.cooldown);
+        }
+    }
+
+    for (entity, mut mower, mut transform) in mowers {
+        match save.mowers.iter().find(|saved| saved.row == mower.row) {
+            Some(saved) => {
+                mower.driving = saved.driving;
+                transform.translation.x = saved.x;
+            }
+            // Used up before saving
+            None => {
+                commands.entity(entity).insert(Dying);
+            }
+        }
+    }
+
+    for plant in &save.plants {
+        let pos = GridPos::new(plant.tile.0, plant.tile.1);
+        let entity = registry.spawn(plant.kind, pos, &mut commands);
+        let mut entity_commands = commands.entity(entity);
+
+        let health = plant.health;
+        entity_commands
+            .entry::<PlayerCommon>()
+            .and_modify(move |mut common| common.health = health);
+        if let Some(price) = plant.price {
+            entity_commands.insert(PlantPrice(price));
+        }
+        SavedStatus::restore_all(&plant.effects, &mut entity_commands);
+        if let Some(timer) = plant.timer {
+            match plant.kind {
+                PlantKind::Peashooter => {
+                    entity_commands
+                        .entry::<Peashooter>()
+                        .and_modify(move |mut shooter| timer.apply(&mut shooter.shoot_timer));
+                }
+                PlantKind::Sunflower => {
+                    entity_commands
+                        .entry::<Sunflower>()
+                        .and_modify(move |mut sunflower| timer.apply(&mut sunflower.produce_timer));
+                }
+                PlantKind::CherryBomb => {
+                    entity_commands
+                        .entry::<CherryBomb>()
+                        .and_modify(move |mut bomb| timer.apply(&mut bomb.fuse_timer));
+                }
+                PlantKind::Wallnut => {}
+            }
+        }
+    }
+
+    for zombie in &save.zombies {
+        let (x, y) = zombie.position;
+        let entity = zombie_registry.spawn(
+            zombie.kind,
+            Vec2::new(x, y).into(),
+            &mut commands,
+            &textures,
+        );
+
+        let health = zombie.health;
+        // Armor that broke before saving breaks again right away
+        let armor = zombie.armor.unwrap_or(0.);
+        let state = match zombie.eating {
+            true => ZombieState::Eating,
+            false => ZombieState::Walking,
+        };
+        let mut entity_commands = commands.entity(entity);
+        entity_commands
+            .entry::<PlayerCommon>()
+            .and_modify(move |mut common| common.health = health);
+        entity_commands
+            .entry::<Transform>()
+            .and_modify(move |mut transform| {
+                transform.translation.x = x;
+                transform.translation.y = y;
+            });
+        entity_commands.queue(Interpolated::teleported);
+        entity_commands
+            .entry::<Armor>()
+            .and_modify(move |mut worn| worn.health = armor);
+        if zombie.vaulted {
+            // Lands right away, losing the pole
+            entity_commands.insert(Vaulting::new(Vec2::new(x, y), x, 0.));
+        }
+        entity_commands
+            .entry::<ZombieCommon>()
+            .and_modify(move |mut common| common.state = state);
+        let bite_timer = zombie.bite_timer;
+        entity_commands
+            .entry::<ZombieStats>()
+            .and_modify(move |mut stats| bite_timer.apply(&mut stats.bite_timer));
+        SavedStatus::restore_all(&zombie.effects, &mut entity_commands);
+    }
+
+    for sun in &save.suns_on_lawn {
+        let transform = Transform::from_translation(sun.position.into());
+        Sun::resting(sun.size, sun.resting).spawn(&mut commands, &textures, transform);
+    }
+
+    for bullet in &save.bullets {
+        let transform = Transform::from_translation((*bullet).into());
+        Bullet::spawn(&mut commands, &textures, transform, None);
+    }
+
+    info!("Restored the game from {SAVE_PATH}");
+}
@@ -0,0 +1,52 @@
+//! Player preferences, kept between runs in a file.
+//!
+//! Change the [Settings] resource and it's written back on its own.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::read());
+        app.add_systems(
+            Update,
+            write_settings
+                .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+        );
+    }
+}
+
+/// Where the settings are kept, relative to the working directory
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// The **Resource** holding the player's preferences
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Draw health bars over players, and the damage they take
+    pub show_health: bool,
+}
+
+impl Settings {
+    /// Read the settings file, or use the defaults without one
+    fn read() -> Settings {
+        let Ok(text) = std::fs::read_to_string(SETTINGS_PATH) else {
+            return Settings::default();
+        };
+        ron::from_str(&text).unwrap_or_else(|err| {
+            warn!("Ignoring {SETTINGS_PATH}: {err}");
+            Settings::default()
+        })
+    }
+}
+
+fn write_settings(settings: Res<Settings>) {
+    let result = ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|text| std::fs::write(SETTINGS_PATH, text).map_err(|err| err.to_string()));
+    if let Err(err) = result {
+        error!("Failed to write {SETTINGS_PATH}: {err}");
+    }
+}
@@ -1,3 +1,5 @@
+use super::level::LoadedLevel;
+use super::save::{PendingSave, SaveGame};
 use crate::{Dying, GameState};
 use bevy::log::info;
 use bevy::prelude::*;
@@ -29,6 +31,47 @@ fn setup_start_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
         ))
         .with_child((Text2d::new("Start"), Transform::from_scale(Vec3::ONE * 5.0)))
         .observe(start_clicked);
+
+    if SaveGame::exists() {
+        commands
+            .spawn((
+                StartScreenComponent,
+                Sprite::from_image(asset_server.load("pause_background.png")),
+                Pickable::default(),
+                Transform::from_xyz(0., -220., 0.1).with_scale(Vec3::ONE * 0.15),
+            ))
+            .with_child((
+                Text2d::new("Continue"),
+                Transform::from_scale(Vec3::ONE * 5.0),
+            ))
+            .observe(continue_clicked);
+    }
+}
+
+fn continue_clicked(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    start_screen_components: Query<Entity, With<StartScreenComponent>>,
+    level: LoadedLevel,
+) {
+    if !level.is_loaded() {
+        warn!("The level is not loaded yet");
+        return;
+    }
+    let save = match SaveGame::read() {
+        Ok(save) => save,
+        Err(err) => {
+            error!("Can't continue the saved game: {err}");
+            return;
+        }
+    };
+    commands.insert_resource(PendingSave(save));
+    next_state.set(GameState::Running);
+
+    for ent in start_screen_components {
+        commands.entity(ent).despawn();
+    }
 }
 
 fn start_clicked(
@@ -36,7 +79,12 @@ fn start_clicked(
     mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
     start_screen_components: Query<Entity, With<StartScreenComponent>>,
+    level: LoadedLevel,
 ) {
+    if !level.is_loaded() {
+        warn!("The level is not loaded yet");
+        return;
+    }
     next_state.set(GameState::Running);
 
     for ent in start_screen_components {
@@ -0,0 +1,222 @@
+//! Timed effects changing how a plant or zombie behaves.
+//!
+//! Every player has [StatusEffects], a stack of [StatusEffect]s that
+//! each wear off on their own. Their speed and eat rate multipliers
+//! are multiplied together, and the strongest effect tints the sprite.
+
+use super::{
+    player::{DamageKind, Damaged, PlayerCommon},
+    zombies::armor::{damage_with_armor, Armor},
+};
+use crate::PauseState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            tick_status_effects.run_if(in_state(PauseState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            tint_status_effects.run_if(in_state(PauseState::Playing)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatusKind {
+    /// Moves and eats at this fraction of the usual speed
+    Slowed(f32),
+    /// Can't do anything
+    Frozen,
+    /// Can't do anything, but isn't frozen
+    Stunned,
+    /// Loses this much health per second
+    Burning(f32),
+}
+
+/// What happens when an effect is added to a player who already has
+/// one of the same kind
+enum Stacking {
+    /// Only one is kept, the strongest one, lasting as long as the longest
+    Refresh,
+    /// They add up, up to this many at once
+    Stack(usize),
+}
+
+impl StatusKind {
+    fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Slowed(_) | StatusKind::Frozen | StatusKind::Stunned => Stacking::Refresh,
+            StatusKind::Burning(_) => Stacking::Stack(3),
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        match self {
+            StatusKind::Slowed(factor) => *factor,
+            StatusKind::Frozen | StatusKind::Stunned => 0.,
+            StatusKind::Burning(_) => 1.,
+        }
+    }
+
+    fn eat_rate(&self) -> f32 {
+        match self {
+            StatusKind::Slowed(factor) => *factor,
+            StatusKind::Frozen | StatusKind::Stunned => 0.,
+            StatusKind::Burning(_) => 1.,
+        }
+    }
+
+    /// Color the sprite is multiplied with, the first effect
+    /// in this order wins
+    fn tint(&self) -> (u8, Color) {
+        match self {
+            StatusKind::Frozen => (0, Color::srgb(0.5, 0.8, 1.)),
+            StatusKind::Stunned => (1, Color::srgb(1., 1., 0.5)),
+            StatusKind::Burning(_) => (2, Color::srgb(1., 0.5, 0.3)),
+            StatusKind::Slowed(_) => (3, Color::srgb(0.6, 0.6, 1.)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub timer: Timer,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, seconds: f32) -> Self {
+        StatusEffect {
+            kind,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+
+    /// Add this effect to a player, following the stacking rules
+    pub fn apply_to(self, entity: &mut EntityCommands) {
+        entity
+            .entry::<StatusEffects>()
+            .and_modify(move |mut effects| effects.add(self));
+    }
+}
+
+/// The stack of effects a player is under
+#[derive(Component, Debug, Default)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn add(&mut self, effect: StatusEffect) {
+        let same_kind: Vec<usize> = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, current)| {
+                std::mem::discriminant(&current.kind) == std::mem::discriminant(&effect.kind)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        match effect.kind.stacking() {
+            Stacking::Refresh => {
+                let Some(&index) = same_kind.first() else {
+                    self.0.push(effect);
+                    return;
+                };
+                let current = &mut self.0[index];
+                if effect.kind.speed() < current.kind.speed() {
+                    current.kind = effect.kind;
+                }
+                if effect.timer.remaining() > current.timer.remaining() {
+                    current.timer = effect.timer;
+                }
+            }
+            Stacking::Stack(max) => {
+                if same_kind.len() >= max {
+                    // Replace the one closest to wearing off
+                    let oldest = same_kind
+                        .into_iter()
+                        .min_by_key(|index| self.0[*index].timer.remaining())
+                        .unwrap();
+                    self.0.remove(oldest);
+                }
+                self.0.push(effect);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+
+    /// Multiplier of the walking speed
+    pub fn speed(&self) -> f32 {
+        self.0.iter().map(|effect| effect.kind.speed()).product()
+    }
+
+    /// Multiplier of the damage done while eating
+    pub fn eat_rate(&self) -> f32 {
+        self.0.iter().map(|effect| effect.kind.eat_rate()).product()
+    }
+
+    fn tint(&self) -> Color {
+        self.0
+            .iter()
+            .map(|effect| effect.kind.tint())
+            .min_by_key(|(order, _)| *order)
+            .map(|(_, color)| color)
+            .unwrap_or(Color::WHITE)
+    }
+}
+
+/// Wear effects off, and burn
+fn tick_status_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    players: Query<(
+        Entity,
+        &mut StatusEffects,
+        &mut PlayerCommon,
+        Option<&mut Armor>,
+    )>,
+) {
+    for (entity, mut effects, mut player, armor) in players {
+        if effects.0.is_empty() {
+            continue;
+        }
+
+        let burn: f32 = effects
+            .0
+            .iter()
+            .map(|effect| match effect.kind {
+                StatusKind::Burning(damage) => damage,
+                _ => 0.,
+            })
+            .sum();
+        if burn > 0. {
+            damage_with_armor(
+                &mut commands,
+                entity,
+                &mut player,
+                armor,
+                Damaged::new(burn * time.delta_secs(), DamageKind::Burn, None),
+            );
+        }
+
+        for effect in &mut effects.0 {
+            effect.timer.tick(time.delta());
+        }
+        effects.0.retain(|effect| !effect.timer.finished());
+    }
+}
+
+fn tint_status_effects(players: Query<(&StatusEffects, &mut Sprite), Changed<StatusEffects>>) {
+    for (effects, mut sprite) in players {
+        sprite.color = effects.tint();
+    }
+}
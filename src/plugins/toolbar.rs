@@ -1,15 +1,24 @@
-use super::{land::LandPlants, plants::PlantCommon, PlayerTextureResources, TOOLBAR_Z};
-use crate::{
-    plugins::{
-        plants::{peashooter::Peashooter, sunflower::Sunflower, wallnut::Wallnut},
-        FLOATING_Z, FLYING_Z,
+use super::{
+    headless::{Headless, TextureLoader},
+    keyboard::GridCursor,
+    land::{LandPlants, LAND_TILE_SIZE},
+    level::LoadedLevel,
+    plants::{
+        registry::{PlantAppearance, PlantRegistry},
+        PlantCommon, PlantKind, PlantPrice,
     },
-    Dying, GameState,
+    TOOLBAR_Z,
+};
+use crate::{
+    plugins::{land::GridPos, FLOATING_Z, FLYING_Z},
+    Dying, GameState, PauseState,
 };
 use bevy::{ecs::system::IntoObserverSystem, text::TextBounds};
 use bevy::{prelude::*, text::cosmic_text::ttf_parser::Style};
 use core::time::Duration;
 use num::traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use vleue_kinetoscope::AnimatedImageController;
 
 pub struct ToolbarPlugin;
 
@@ -17,15 +26,28 @@ impl Plugin for ToolbarPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Running), setup);
         app.add_systems(PreStartup, (ToolbarTextureResource::setup, setup_suncount));
+        app.init_resource::<ShovelRefund>();
+        app.add_observer(plant_action);
+        app.add_observer(pick_card);
+        app.add_observer(dig_action);
+        // Availability decides what can be planted, so it's gameplay
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
-                follow_mouse,
                 sun_changed.run_if(resource_changed::<SunCount>),
                 update_cooldown_secs,
-                availability_changed,
             )
-                .run_if(in_state(GameState::Running)),
+                .run_if(in_state(PauseState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            availability_changed.run_if(in_state(PauseState::Playing)),
+        );
+        // Nobody points or types in the headless simulation
+        app.add_systems(
+            Update,
+            (follow_mouse, snap_ghosts, place_ghost, cancel_selection)
+                .run_if(in_state(PauseState::Playing).and(not(resource_exists::<Headless>))),
         );
     }
 }
@@ -33,31 +55,28 @@ impl Plugin for ToolbarPlugin {
 #[derive(Resource)]
 struct ToolbarTextureResource {
     pub counter: Handle<Image>,
-    pub sunflower_card: Handle<Image>,
-    pub wallnut_card: Handle<Image>,
-    pub peashooter_card: Handle<Image>,
-    pub cherrybomb_card: Handle<Image>,
+    pub shovel: Handle<Image>,
 }
 
 impl ToolbarTextureResource {
-    pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    pub fn setup(mut commands: Commands, asset_server: TextureLoader) {
         commands.insert_resource(ToolbarTextureResource {
             counter: asset_server.load("Counter.png"),
-            sunflower_card: asset_server.load("SunflowerCard.PNG"),
-            wallnut_card: asset_server.load("WallNutCard.png"),
-            peashooter_card: asset_server.load("PeashooterCard.PNG"),
-            cherrybomb_card: asset_server.load("CherryBombCard.png"),
+            shovel: asset_server.load("Shovel.jpg"),
         })
     }
 }
 
 /// Marker component for the sun counter
 #[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
 pub struct SunCounter;
 
 /// Marker component for a button (a plant) in the toolbar
 #[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
 pub struct ToolbarPlant {
+    pub kind: PlantKind,
     pub price: i32,
     pub cooldown: Timer,
 }
@@ -82,27 +101,60 @@ impl PlantAvailabilityState {
 /// Any entity with this component will be positionsed
 /// where the mouse pointer is
 #[derive(Component)]
-#[require(Transform)]
+#[require(Transform, StateScoped<GameState> = StateScoped(GameState::Running))]
 struct FollowMouse;
 
+/// Marker component for what was picked from the toolbar and
+/// not used yet, a [PlacementGhost] or the floating shovel.
+///
+/// Right-click or Esc drops it.
+#[derive(Component)]
+pub struct ToolbarSelection;
+
+/// A see-through plant showing where a picked card will be planted.
+///
+/// Snapped to the tile under the mouse or the [GridCursor], with a
+/// [TileHighlight] telling whether it can be planted there.
+#[derive(Component)]
+#[require(
+    ToolbarSelection = ToolbarSelection,
+    Transform,
+    Visibility,
+    Pickable = Pickable::IGNORE,
+    StateScoped<GameState> = StateScoped(GameState::Running)
+)]
+pub struct PlacementGhost {
+    pub kind: PlantKind,
+}
+
+/// The tile under a [PlacementGhost], green when the plant can
+/// go there and red when it can't
+#[derive(Component)]
+struct TileHighlight;
+
+const GHOST_COLOR: Color = Color::srgba(1., 1., 1., 0.5);
+const VALID_TILE_COLOR: Color = Color::srgba(0.2, 0.9, 0.2, 0.35);
+const INVALID_TILE_COLOR: Color = Color::srgba(0.9, 0.2, 0.2, 0.35);
+
 /// The **Resource** representing how many suns we
 /// currently have. Used to buy plants.
 #[derive(Resource)]
 pub struct SunCount(pub i32);
 
 fn setup_suncount(mut commands: Commands) {
-    #[cfg(feature = "debug_mode")]
-    commands.insert_resource(SunCount(5000));
-    #[cfg(not(feature = "debug_mode"))]
-    commands.insert_resource(SunCount(50));
+    commands.insert_resource(SunCount(0));
 }
 
 fn setup(
     mut commands: Commands,
     textures: Res<ToolbarTextureResource>,
+    registry: Res<PlantRegistry>,
     mut sun_count: ResMut<SunCount>,
+    level: LoadedLevel,
 ) {
-    sun_count.0 = sun_count.0; // Trigger sun_changed
+    let level = level.get();
+    sun_count.0 = level.initial_suns; // Also triggers sun_changed
+    commands.insert_resource(ShovelRefund(level.shovel_refund));
     let counter_transform = Transform::from_xyz(-400. + 163. / 2., 300. - 48. / 2., TOOLBAR_Z);
     commands.spawn((
         SunCounter,
@@ -116,45 +168,28 @@ fn setup(
     const WIDTH: f32 = 110.;
     let mut x = left + WIDTH / 2.;
 
-    add_toolbar_item(
-        &mut commands,
-        &mut x,
-        textures.sunflower_card.clone(),
-        50,
-        5,
-        Sunflower::create,
-    );
-
-    add_toolbar_item(
-        &mut commands,
-        &mut x,
-        textures.peashooter_card.clone(),
-        100,
-        10,
-        Peashooter::create,
-    );
-
-    add_toolbar_item(
-        &mut commands,
-        &mut x,
-        textures.wallnut_card.clone(),
-        50,
-        5,
-        Wallnut::create,
-    );
+    for kind in &level.cards {
+        let plant = registry.get(*kind);
+        add_toolbar_item(
+            &mut commands,
+            &mut x,
+            *kind,
+            plant.card.clone(),
+            plant.price,
+            plant.recharge,
+        );
+    }
+
+    add_shovel(&mut commands, &mut x, textures.shovel.clone());
 }
 
 fn add_toolbar_item(
     commands: &mut Commands,
     x: &mut f32,
+    kind: PlantKind,
     card_texture: Handle<Image>,
     price: i32,
     cooldown_time: impl ToPrimitive + std::fmt::Display,
-    plant_fn: impl Fn(Vec2, &mut Commands, Res<PlayerTextureResources>) -> ()
-        + Sync
-        + Send
-        + 'static
-        + Clone,
 ) {
     const HEIGHT: f32 = 70.;
     const WIDTH: f32 = 110.;
@@ -169,13 +204,17 @@ fn add_toolbar_item(
 
     commands
         .spawn((
-            ToolbarPlant { price, cooldown },
+            ToolbarPlant {
+                kind,
+                price,
+                cooldown,
+            },
             PlantAvailabilityState {
                 cooldown_finished: true,
                 sun_enough: false,
             },
             Sprite {
-                image: card_texture.clone(),
+                image: card_texture,
                 color: Color::linear_rgb(0.5, 0.5, 0.5),
                 ..default()
             },
@@ -183,100 +222,224 @@ fn add_toolbar_item(
             Transform::from_xyz(*x, y, TOOLBAR_Z),
             Pickable::default(),
         ))
-        .observe(tb_gen_observer(card_texture.clone(), plant_fn));
+        .observe(card_clicked);
     *x += WIDTH;
 }
 
-/// # Toolbar generate observer
-/// Generates an observer for a toolbar button (e.g. a sunflower).
+/// The **Resource** deciding how many suns you get back
+/// when digging up a plant with the shovel.
 ///
-/// What it does:
-/// - Creates a floating card when clicked
-/// - When clicked again
-///     - If empty, plant the plant
-///     - Remove the floating card
+/// It's a fraction of the price paid for that plant,
+/// e.g. 0.5 gives back half of it. Set from the level's
+/// `shovel_refund`, and defaults to no refund.
+#[derive(Resource, Default)]
+pub struct ShovelRefund(pub f32);
+
+/// Marker component for the shovel slot in the toolbar
+#[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
+pub struct ToolbarShovel;
+
+fn add_shovel(commands: &mut Commands, x: &mut f32, texture: Handle<Image>) {
+    const HEIGHT: f32 = 70.;
+    const WIDTH: f32 = 110.;
+    let y = 300. - HEIGHT / 2.;
+
+    commands
+        .spawn((
+            ToolbarShovel,
+            Sprite::from_image(texture),
+            Transform::from_xyz(*x, y, TOOLBAR_Z).with_scale(Vec3::splat(0.45)),
+            Pickable::default(),
+        ))
+        .observe(shovel_clicked);
+    *x += WIDTH;
+}
+
+/// Observer for the shovel slot in the toolbar.
 ///
-/// ## Arguments
-/// - float_image: The image to show in the floating widget that follows
-/// the mouse when toolbar button (e.g. sunflower) is clicked
-/// - plant_fn: A closure that actually puts the plant onto the land.
-/// Only called when it's empty there.
-fn tb_gen_observer(
-    float_image: Handle<Image>,
-    plant_fn: impl Fn(Vec2, &mut Commands, Res<PlayerTextureResources>) -> ()
-        + Sync
-        + Send
-        + 'static
-        + Clone,
-) -> impl Fn(
-    Trigger<Pointer<Click>>,
-    Commands,
-    Query<&PlantAvailabilityState>,
-    Query<&ToolbarPlant>,
-    Single<(&Camera, &GlobalTransform)>,
-    Res<SunCount>,
+/// Creates a floating shovel that follows the mouse,
+/// which digs up the plant under it when clicked.
+fn shovel_clicked(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    shovels: Query<(&Sprite, &Transform), With<ToolbarShovel>>,
 ) {
-    // The observer for toolbar click
-    move |trigger: Trigger<Pointer<Click>>,
-          mut commands: Commands,
-          plant_availability: Query<&PlantAvailabilityState>,
-          plant_commmon: Query<&ToolbarPlant>,
-          camera: Single<(&Camera, &GlobalTransform)>,
-          sun_count: Res<SunCount>| {
-        let event = trigger.event();
-        let mouse_pos_raw = event.pointer_location.clone();
-        let (camera, camera_transform) = *camera;
-        let mouse_pos = camera
-            .viewport_to_world(camera_transform, mouse_pos_raw.position)
-            .unwrap()
-            .origin
-            .truncate();
-
-        let availability = plant_availability.get(trigger.target()).unwrap();
-        if availability.available() == false {
-            return;
-        }
+    let (sprite, transform) = shovels.get(trigger.target()).unwrap();
+    let mut float_transform = transform.clone();
+    float_transform.translation.z = FLOATING_Z;
+
+    commands
+        .spawn((
+            Sprite::from_image(sprite.image.clone()),
+            float_transform,
+            Pickable::default(),
+            FollowMouse,
+            ToolbarSelection,
+        ))
+        .observe(dig_clicked);
+}
+
+/// Observer for the floating shovel.
+///
+/// Triggers a [DigAction] on the tile under the shovel,
+/// and removes the floating shovel.
+fn dig_clicked(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    camera: Single<(&Camera, &GlobalTransform)>,
+) {
+    let event = trigger.event();
+    let (camera, camera_transform) = *camera;
+    let mouse_pos = camera
+        .viewport_to_world(camera_transform, event.pointer_location.position)
+        .unwrap()
+        .origin
+        .truncate();
+
+    let tile: GridPos = mouse_pos.into();
+    commands.trigger(DigAction { tile: tile.into() });
+    commands.entity(trigger.target()).insert(Dying);
+}
 
-        let toolbar_plant = plant_commmon.get(trigger.target()).unwrap();
-        let price = toolbar_plant.price;
-        let cloned_plant_fn = plant_fn.clone();
-        let toolbar_plant_entity = trigger.target();
-        // Spawn the floating widget
-        commands
-            .spawn((
-                Sprite::from_image(float_image.clone()),
-                Transform::from_xyz(mouse_pos.x, mouse_pos.y, FLOATING_Z),
-                Pickable::default(),
-                FollowMouse,
-            ))
-            .observe(
-                // Observer for the floating widget
-                move |trigger: Trigger<Pointer<Click>>,
-                      mut commands: Commands,
-                      textures: Res<PlayerTextureResources>,
-                      camera: Single<(&Camera, &GlobalTransform)>,
-                      map: Res<LandPlants>,
-                      mut sun_count: ResMut<SunCount>,
-                      mut plant: Query<&mut ToolbarPlant>| {
-                    let event = trigger.event();
-                    let mouse_pos_raw = event.pointer_location.clone();
-                    let (camera, camera_transform) = *camera;
-                    let mouse_pos = camera
-                        .viewport_to_world(camera_transform, mouse_pos_raw.position)
-                        .unwrap()
-                        .origin
-                        .truncate();
-
-                    if map.is_empty(mouse_pos.into()) {
-                        cloned_plant_fn(mouse_pos, &mut commands, textures);
-                        sun_count.0 -= price;
-                        let mut tb_plant = plant.get_mut(toolbar_plant_entity).unwrap();
-                        tb_plant.cooldown.reset();
-                    }
-                    commands.entity(trigger.target()).insert(Dying);
-                },
-            );
+/// Dig up the plant on a tile with the shovel.
+///
+/// Part of its price is refunded according to [ShovelRefund].
+/// Nothing happens if there's no plant on the tile.
+#[derive(Event, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DigAction {
+    pub tile: (i32, i32),
+}
+
+fn dig_action(
+    trigger: Trigger<DigAction>,
+    mut commands: Commands,
+    map: Res<LandPlants>,
+    refund: Res<ShovelRefund>,
+    mut sun_count: ResMut<SunCount>,
+    prices: Query<&PlantPrice>,
+) {
+    let Some(plant) = map.get(trigger.event().tile) else {
+        return;
+    };
+    if let Ok(price) = prices.get(*plant) {
+        sun_count.0 += (price.0 as f32 * refund.0).floor() as i32;
     }
+    // Removing the plant releases its land tile through
+    // the PlantCommon on_remove hook. It's despawned right away
+    // rather than marked Dying, so the tile is free before the
+    // next tick whether the dig comes from a click or a replay.
+    commands.entity(*plant).despawn();
+}
+
+/// Put a plant from the toolbar onto the land.
+///
+/// This is what clicking a card and then a tile does, so it
+/// can be driven without a mouse (e.g. in a headless simulation).
+/// Nothing happens if the card isn't available or the tile isn't empty.
+#[derive(Event, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlantAction {
+    pub kind: PlantKind,
+    pub tile: (i32, i32),
+}
+
+fn plant_action(
+    trigger: Trigger<PlantAction>,
+    mut commands: Commands,
+    registry: Res<PlantRegistry>,
+    map: Res<LandPlants>,
+    mut sun_count: ResMut<SunCount>,
+    mut toolbar_plants: Query<(&mut ToolbarPlant, &PlantAvailabilityState)>,
+) {
+    let PlantAction { kind, tile } = *trigger.event();
+    let Some((mut toolbar_plant, availability)) = toolbar_plants
+        .iter_mut()
+        .find(|(toolbar_plant, _)| toolbar_plant.kind == kind)
+    else {
+        return;
+    };
+
+    let pos = GridPos::new(tile.0, tile.1);
+    if !availability.available() || !map.is_empty(pos) {
+        return;
+    }
+
+    let plant_entity = registry.spawn(kind, pos, &mut commands);
+    commands
+        .entity(plant_entity)
+        .insert(PlantPrice(toolbar_plant.price));
+    sun_count.0 -= toolbar_plant.price;
+    toolbar_plant.cooldown.reset();
+}
+
+/// Pick the targeted plant card from the toolbar.
+///
+/// Clicking a card triggers this, and so do the number keys.
+/// It shows a [PlacementGhost] of the plant until it's planted
+/// or dropped. Nothing happens if the card isn't available.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PickCard;
+
+fn card_clicked(trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger_targets(PickCard, trigger.target());
+}
+
+fn pick_card(
+    trigger: Trigger<PickCard>,
+    mut commands: Commands,
+    registry: Res<PlantRegistry>,
+    cards: Query<(&ToolbarPlant, &PlantAvailabilityState)>,
+    selections: Query<Entity, With<ToolbarSelection>>,
+) {
+    let Ok((card, availability)) = cards.get(trigger.target()) else {
+        return;
+    };
+    if !availability.available() {
+        return;
+    }
+
+    // Only one thing can be picked at a time
+    for selection in &selections {
+        commands.entity(selection).insert(Dying);
+    }
+
+    // Placed by snap_ghosts before it's drawn
+    let mut ghost = commands.spawn((
+        PlacementGhost { kind: card.kind },
+        Transform::from_xyz(0., 0., FLOATING_Z),
+    ));
+    match &registry.get(card.kind).appearance {
+        PlantAppearance::Animated(animation) => ghost.insert((
+            AnimatedImageController::play(animation.clone()),
+            Sprite {
+                color: GHOST_COLOR,
+                ..default()
+            },
+        )),
+        PlantAppearance::Still(image) => ghost.insert(Sprite {
+            color: GHOST_COLOR,
+            ..Sprite::from_image(image.clone())
+        }),
+    };
+    ghost.with_child((
+        TileHighlight,
+        Sprite::from_color(VALID_TILE_COLOR, LAND_TILE_SIZE),
+        // Under the plant
+        Transform::from_xyz(0., 0., -0.5),
+        Pickable::IGNORE,
+    ));
+}
+
+/// Where the mouse pointer is in the world, if it's over the window
+fn cursor_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
+        .map(|ray| ray.origin.truncate())
 }
 
 fn follow_mouse(
@@ -288,11 +451,7 @@ fn follow_mouse(
         return;
     }
     let (camera, camera_transform) = *camera;
-    let Some(mouse_pos) = window
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
-        .map(|ray| ray.origin.truncate())
-    else {
+    let Some(mouse_pos) = cursor_position(&window, camera, camera_transform) else {
         return;
     };
 
@@ -302,6 +461,103 @@ fn follow_mouse(
     }
 }
 
+/// Snap ghosts to the tile under the mouse, or under the
+/// [GridCursor] when the keyboard was used last, and color the
+/// tile by whether a [PlantAction] would plant there.
+///
+/// Off the land, ghosts follow the mouse with no tile shown.
+fn snap_ghosts(
+    ghosts: Query<(&PlacementGhost, &mut Transform, &Children)>,
+    mut highlights: Query<(&mut Sprite, &mut Visibility), With<TileHighlight>>,
+    cards: Query<(&ToolbarPlant, &PlantAvailabilityState)>,
+    map: Res<LandPlants>,
+    grid_cursor: Option<Res<GridCursor>>,
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+) {
+    let (camera, camera_transform) = *camera;
+    let pointed = match grid_cursor.filter(|cursor| cursor.active) {
+        Some(cursor) => {
+            let tile = GridPos::new(cursor.tile.0, cursor.tile.1);
+            Some(tile.to_world())
+        }
+        None => cursor_position(&window, camera, camera_transform),
+    };
+    let Some(pointed) = pointed else {
+        return;
+    };
+    let tile = GridPos::from(pointed).round();
+    let on_land = tile.in_land();
+
+    for (ghost, mut transform, children) in ghosts {
+        let position = if on_land { tile.to_world() } else { pointed };
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        let available = cards
+            .iter()
+            .any(|(card, availability)| card.kind == ghost.kind && availability.available());
+        for child in children.iter() {
+            let Ok((mut sprite, mut visibility)) = highlights.get_mut(child) else {
+                continue;
+            };
+            *visibility = match on_land {
+                true => Visibility::Inherited,
+                false => Visibility::Hidden,
+            };
+            sprite.color = match available && map.is_empty(tile) {
+                true => VALID_TILE_COLOR,
+                false => INVALID_TILE_COLOR,
+            };
+        }
+    }
+}
+
+/// Trigger a [PlantAction] on the tile under a ghost when
+/// the mouse is pressed, and remove the ghost.
+///
+/// Like with any [PlantAction], nothing is planted
+/// if the tile can't be planted on.
+fn place_ghost(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    ghosts: Query<(Entity, &PlacementGhost)>,
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let (camera, camera_transform) = *camera;
+    let mouse_pos = cursor_position(&window, camera, camera_transform);
+
+    for (entity, ghost) in ghosts {
+        if let Some(mouse_pos) = mouse_pos {
+            let tile: GridPos = mouse_pos.into();
+            commands.trigger(PlantAction {
+                kind: ghost.kind,
+                tile: tile.into(),
+            });
+        }
+        commands.entity(entity).insert(Dying);
+    }
+}
+
+/// Drop what was picked from the toolbar on right-click or Esc
+fn cancel_selection(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    selections: Query<Entity, With<ToolbarSelection>>,
+) {
+    if !mouse.just_pressed(MouseButton::Right) && !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    for selection in selections {
+        commands.entity(selection).insert(Dying);
+    }
+}
+
 fn sun_changed(
     mut counter: Single<&mut Text2d, With<SunCounter>>,
     sun_count: Res<SunCount>,
@@ -0,0 +1,88 @@
+//! Armor worn by zombies, like the cone of the conehead.
+//!
+//! Armor takes damage before the zombie's own health. Once it's
+//! broken it falls off, and the zombie looks like a bare one.
+
+use super::ZombieCommon;
+use crate::plugins::player::{Damaged, PlayerCommon};
+use bevy::{log::debug, prelude::*};
+use vleue_kinetoscope::AnimatedImage;
+
+/// What an armor is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmorMaterial {
+    /// Cones
+    Plastic,
+    /// Buckets and screen doors, pulled away by magnets
+    Metal,
+}
+
+#[derive(Component)]
+pub struct Armor {
+    pub health: f32,
+    pub material: ArmorMaterial,
+    /// Walking animation once the armor falls off
+    pub bare_walking: Handle<AnimatedImage>,
+    /// Eating animation once the armor falls off
+    pub bare_eating: Handle<AnimatedImage>,
+}
+
+impl Armor {
+    pub fn new(
+        material: ArmorMaterial,
+        health: f32,
+        bare_walking: Handle<AnimatedImage>,
+        bare_eating: Handle<AnimatedImage>,
+    ) -> Self {
+        Armor {
+            health,
+            material,
+            bare_walking,
+            bare_eating,
+        }
+    }
+}
+
+/// Damage a player, through its armor first if it wears one.
+///
+/// What the armor can't take goes to the player's health.
+/// [Damaged] is triggered either way, with what the armor
+/// absorbed, so hits on armor are seen too.
+pub fn damage_with_armor(
+    commands: &mut Commands,
+    target: Entity,
+    player: &mut PlayerCommon,
+    armor: Option<Mut<Armor>>,
+    damage: Damaged,
+) {
+    let mut absorbed = 0.;
+    if let Some(mut armor) = armor.filter(|armor| armor.health > 0.) {
+        absorbed = damage.amount.min(armor.health);
+        armor.health -= absorbed;
+    }
+    player.damage(
+        commands,
+        target,
+        Damaged {
+            amount: damage.amount - absorbed,
+            absorbed,
+            ..damage
+        },
+    );
+}
+
+/// Drop broken armor, and show the zombie without it
+pub fn drop_broken_armor(
+    mut commands: Commands,
+    zombies: Query<(Entity, &Armor, &mut ZombieCommon), Changed<Armor>>,
+) {
+    for (entity, armor, mut common) in zombies {
+        if armor.health > 0. {
+            continue;
+        }
+        debug!("{:?} armor of {entity} broke", armor.material);
+        common.walking = armor.bare_walking.clone();
+        common.eating = armor.bare_eating.clone();
+        commands.entity(entity).remove::<Armor>();
+    }
+}
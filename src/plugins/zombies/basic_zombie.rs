@@ -1,4 +1,4 @@
-use super::{super::PLAYERS_Z, ZombieCommon};
+use super::{super::PLAYERS_Z, create_zombie::ZombieType, ZombieCommon, ZombieStats};
 use crate::plugins::{
     land::{LAND_SIZE, LAND_TILE_SIZE},
     player::PlayerCommon,
@@ -14,17 +14,25 @@ use vleue_kinetoscope::{
 pub struct BasicZombie;
 
 impl BasicZombie {
-    pub fn create(pos: GridPos, commands: &mut Commands, textures: &Res<PlayerTextureResources>) {
-        commands.spawn((
-            PlayerCommon::new("BasicZombie", 100.),
-            ZombieCommon::new(
-                textures.basic_zombie.clone(),
-                textures.eating_zombie.clone(),
-            ),
-            BasicZombie,
-            AnimatedImageController::play(textures.basic_zombie.clone()),
-            pos.round()
-                .to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
-        ));
+    pub fn create(
+        pos: GridPos,
+        commands: &mut Commands,
+        textures: &PlayerTextureResources,
+    ) -> Entity {
+        commands
+            .spawn((
+                PlayerCommon::new("BasicZombie", 100.),
+                ZombieCommon::new(
+                    textures.basic_zombie.clone(),
+                    textures.eating_zombie.clone(),
+                ),
+                BasicZombie,
+                ZombieStats::new(10., 5., 0.5),
+                ZombieType::Basic,
+                AnimatedImageController::play(textures.basic_zombie.clone()),
+                pos.round()
+                    .to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
+            ))
+            .id()
     }
 }
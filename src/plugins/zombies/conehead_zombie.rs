@@ -1,4 +1,9 @@
-use super::{super::PLAYERS_Z, ZombieCommon};
+use super::{
+    super::PLAYERS_Z,
+    armor::{Armor, ArmorMaterial},
+    create_zombie::ZombieType,
+    ZombieCommon, ZombieStats,
+};
 use crate::plugins::{
     land::{LAND_SIZE, LAND_TILE_SIZE},
     player::PlayerCommon,
@@ -14,16 +19,30 @@ use vleue_kinetoscope::{
 pub struct ConeheadZombie;
 
 impl ConeheadZombie {
-    pub fn create(pos: GridPos, commands: &mut Commands, textures: &Res<PlayerTextureResources>) {
-        commands.spawn((
-            PlayerCommon::new("Conehead Zombie", 200.),
-            ZombieCommon::new(
-                textures.conehead_zombie.clone(),
-                textures.eating_conehead_zombie.clone(),
-            ),
-            ConeheadZombie,
-            AnimatedImageController::play(textures.conehead_zombie.clone()),
-            pos.to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
-        ));
+    pub fn create(
+        pos: GridPos,
+        commands: &mut Commands,
+        textures: &PlayerTextureResources,
+    ) -> Entity {
+        commands
+            .spawn((
+                PlayerCommon::new("Conehead Zombie", 100.),
+                ZombieCommon::new(
+                    textures.conehead_zombie.clone(),
+                    textures.eating_conehead_zombie.clone(),
+                ),
+                ConeheadZombie,
+                Armor::new(
+                    ArmorMaterial::Plastic,
+                    100.,
+                    textures.basic_zombie.clone(),
+                    textures.eating_zombie.clone(),
+                ),
+                ZombieStats::new(9., 6., 0.6),
+                ZombieType::Conehead,
+                AnimatedImageController::play(textures.conehead_zombie.clone()),
+                pos.to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
+            ))
+            .id()
     }
 }
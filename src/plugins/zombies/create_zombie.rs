@@ -1,86 +1,199 @@
-use super::{
-    super::PLAYERS_Z, basic_zombie::BasicZombie, conehead_zombie::ConeheadZombie, ZombieCommon,
-};
+use super::{registry::ZombieRegistry, ZombieCommon};
 use crate::{
     plugins::{
-        land::{LAND_SIZE, LAND_TILE_SIZE},
-        player::PlayerCommon,
-        zombies::jumping_zombie::JumpingZombie,
-        GridPos, PlayerTextureResources,
+        land::LAND_SIZE, level::LoadedLevel, rng::GameRng, GridPos, PlayerTextureResources,
+        FLOATING_Z,
     },
-    GameState,
+    Dying, GameState,
 };
-use bevy::log::info;
 use bevy::prelude::*;
 use core::time::Duration;
-use num::traits::real::Real;
-use vleue_kinetoscope::{
-    AnimatedImage, AnimatedImageController, AnimatedImagePlugin, AnimationPlayed,
-};
+use serde::{Deserialize, Serialize};
 
-#[cfg(not(feature = "debug_mode"))]
-const INITIAL_INTERVAL: f32 = 10.;
-#[cfg(feature = "debug_mode")]
-const INITIAL_INTERVAL: f32 = 3.;
+/// How long the "huge wave is approaching" banner is shown
+/// before the huge wave arrives
+const BANNER_SECONDS: f32 = 4.;
 
+/// Timer until the next wave is sent
 #[derive(Resource)]
 pub struct ZombieCreateTimer {
-    timer: Timer,
+    pub timer: Timer,
+}
+
+/// The **Resource** deciding which waves of zombies are sent
+#[derive(Resource)]
+pub struct WaveDirector {
+    /// How many waves have been sent so far
+    pub wave: u32,
+    pub total_waves: u32,
+    pub huge_wave_every: u32,
+    /// Whether the banner for the upcoming huge wave has been shown
+    pub huge_wave_warned: bool,
+    /// How many zombies were sent in the last wave
+    pub last_wave_size: u32,
+    /// Seconds between two waves
+    pub wave_interval: f32,
+    /// Zombies that may be sent
+    pub zombies: Vec<ZombieType>,
+    /// How many rows zombies walk on
+    pub rows: u32,
 }
 
-pub fn setup(mut commands: Commands) {
+impl WaveDirector {
+    pub fn is_huge_wave(&self, wave: u32) -> bool {
+        wave % self.huge_wave_every == 0
+    }
+
+    /// How many points the zombies in a wave may cost in total
+    pub fn budget(&self, wave: u32) -> u32 {
+        let budget = 1 + wave * 2 / 3;
+        if self.is_huge_wave(wave) {
+            budget * 5 / 2
+        } else {
+            budget
+        }
+    }
+
+    /// All waves have been sent
+    pub fn finished(&self) -> bool {
+        self.wave >= self.total_waves
+    }
+
+    /// How far into the level we are, from 0 to 1.
+    ///
+    /// Every wave fills an equal share of it: half of the share
+    /// when the wave is sent, and the rest as zombies are killed.
+    pub fn progress(&self, alive_zombies: usize) -> f32 {
+        if self.wave == 0 {
+            return 0.;
+        }
+        let share = 1. / self.total_waves as f32;
+        let killed = 1. - alive_zombies as f32 / self.last_wave_size.max(1) as f32;
+
+        share * (self.wave - 1) as f32 + share * 0.5 + share * 0.5 * killed.clamp(0., 1.)
+    }
+}
+
+pub fn setup(mut commands: Commands, level: LoadedLevel) {
+    let level = level.get();
     commands.insert_resource(ZombieCreateTimer {
-        timer: Timer::from_seconds(INITIAL_INTERVAL, TimerMode::Repeating),
+        timer: Timer::from_seconds(level.first_wave_delay, TimerMode::Once),
+    });
+    commands.insert_resource(WaveDirector {
+        wave: 0,
+        total_waves: level.total_waves,
+        huge_wave_every: level.huge_wave_every,
+        huge_wave_warned: false,
+        last_wave_size: 0,
+        wave_interval: level.wave_interval,
+        zombies: level.zombies.clone(),
+        rows: level.rows(),
     });
 }
 
-#[derive(Debug)]
-enum ZombieType {
+/// Kinds of zombies that can be sent in waves.
+/// See [ZombieRegistry] for when they are sent.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ZombieType {
     Basic,
     Conehead,
     Jumping,
+    Flag,
 }
 
-#[cfg(feature = "debug_mode")]
-const WIN_SECONDS: u64 = 60 * 1;
-#[cfg(not(feature = "debug_mode"))]
-const WIN_SECONDS: u64 = 60 * 10;
+/// Marker component for the "huge wave is approaching" banner
+#[derive(Component)]
+#[require(StateScoped<GameState> = StateScoped(GameState::Running))]
+pub struct HugeWaveBanner {
+    timer: Timer,
+}
 
-pub fn create_zombie_randomly(
+/// Send waves of zombies, and win the level when the last one is cleared
+pub fn send_waves(
     mut commands: Commands,
     time: Res<Time>,
     mut timer: ResMut<ZombieCreateTimer>,
+    mut director: ResMut<WaveDirector>,
     textures: Res<PlayerTextureResources>,
+    registry: Res<ZombieRegistry>,
+    mut rng: ResMut<GameRng>,
+    zombies: Query<(), With<ZombieCommon>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    #[cfg(not(feature = "debug_mode"))]
-    if time.elapsed().as_secs() < 45 {
+    if director.finished() {
+        if zombies.is_empty() {
+            next_state.set(GameState::End { win: true });
+        }
         return;
     }
 
-    if time.elapsed().as_secs() > WIN_SECONDS {
-        next_state.set(GameState::End { win: true });
+    timer.timer.tick(time.delta());
+    if !timer.timer.finished() {
+        return;
     }
 
-    timer.timer.tick(time.delta());
-    if timer.timer.finished() {
-        let zombie_type: &ZombieType =
-            fastrand::choice(&[ZombieType::Basic, ZombieType::Conehead, ZombieType::Jumping])
-                .unwrap();
-        let row = fastrand::i32(0..(LAND_SIZE.y as i32));
-        let pos = GridPos::new(LAND_SIZE.x + 2., row);
-
-        match zombie_type {
-            ZombieType::Basic => BasicZombie::create(pos, &mut commands, &textures),
-            ZombieType::Conehead => ConeheadZombie::create(pos, &mut commands, &textures),
-            ZombieType::Jumping => JumpingZombie::create(pos, &mut commands, &textures),
-        }
+    let wave = director.wave + 1;
+    let huge = director.is_huge_wave(wave);
+    if huge && !director.huge_wave_warned {
+        // Show the banner and give the player a moment to prepare
+        director.huge_wave_warned = true;
+        commands.spawn((
+            HugeWaveBanner {
+                timer: Timer::from_seconds(BANNER_SECONDS, TimerMode::Once),
+            },
+            Text2d::new("A huge wave of zombies is approaching!"),
+            TextColor(Color::srgb(1., 0.1, 0.1)),
+            Transform::from_xyz(0., 0., FLOATING_Z),
+        ));
+        restart_timer(&mut timer, BANNER_SECONDS);
+        return;
+    }
+
+    let mut wave_size = 0;
+    if let Some(leader) = registry.leader(&director.zombies).filter(|_| huge) {
+        wave_size += 1;
+        let row = rng.u32(0..director.rows);
+        registry.spawn(
+            leader,
+            GridPos::new(LAND_SIZE.x + 1.5, row),
+            &mut commands,
+            &textures,
+        );
+    }
+
+    let mut budget = director.budget(wave);
+    while let Some((zombie_type, row)) =
+        registry.sample(&director.zombies, wave, budget, director.rows, &mut rng)
+    {
+        budget -= registry.get(zombie_type).cost;
+        wave_size += 1;
 
-        let x = time.elapsed().as_secs_f32();
-        let lower = ((1. / 50.) * x + 1.).powf(0.3);
-        let new_duration = (1. / lower) * INITIAL_INTERVAL;
-        timer
-            .timer
-            .set_duration(Duration::from_secs_f32(new_duration));
+        // Spread the zombies a bit so they don't walk in one pile
+        let pos = GridPos::new(LAND_SIZE.x + 2. + rng.f32() * 1.5, row);
+        registry.spawn(zombie_type, pos, &mut commands, &textures);
+    }
+
+    director.wave = wave;
+    director.huge_wave_warned = false;
+    director.last_wave_size = wave_size;
+    let wave_interval = director.wave_interval;
+    restart_timer(&mut timer, wave_interval);
+}
+
+fn restart_timer(timer: &mut ZombieCreateTimer, seconds: f32) {
+    timer.timer.set_duration(Duration::from_secs_f32(seconds));
+    timer.timer.reset();
+}
+
+pub fn remove_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    banners: Query<(Entity, &mut HugeWaveBanner)>,
+) {
+    for (entity, mut banner) in banners {
+        banner.timer.tick(time.delta());
+        if banner.timer.finished() {
+            commands.entity(entity).insert(Dying);
+        }
     }
 }
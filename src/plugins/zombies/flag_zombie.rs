@@ -0,0 +1,37 @@
+use super::{super::PLAYERS_Z, create_zombie::ZombieType, ZombieCommon, ZombieStats};
+use crate::plugins::{land::LAND_SIZE, player::PlayerCommon, GridPos, PlayerTextureResources};
+use bevy::prelude::*;
+
+/// The zombie leading a huge wave
+#[derive(Component)]
+pub struct FlagZombie {
+    /// There's no walking animation for the flag zombie, so it's
+    /// shown as this still image while walking, and eats like
+    /// the basic zombie
+    pub walking: Handle<Image>,
+}
+
+impl FlagZombie {
+    pub fn create(
+        pos: GridPos,
+        commands: &mut Commands,
+        textures: &PlayerTextureResources,
+    ) -> Entity {
+        commands
+            .spawn((
+                PlayerCommon::new("Flag Zombie", 100.),
+                ZombieCommon::new(
+                    textures.basic_zombie.clone(),
+                    textures.eating_zombie.clone(),
+                ),
+                FlagZombie {
+                    walking: textures.flag_zombie.clone(),
+                },
+                ZombieStats::new(12., 5., 0.5),
+                ZombieType::Flag,
+                Sprite::from_image(textures.flag_zombie.clone()),
+                pos.to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
+            ))
+            .id()
+    }
+}
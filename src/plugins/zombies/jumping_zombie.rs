@@ -1,62 +1,192 @@
-use super::{super::PLAYERS_Z, ZombieCommon, ZombieState};
+use super::{super::PLAYERS_Z, create_zombie::ZombieType, ZombieCommon, ZombieStats};
 use crate::plugins::{
-    land::{LandPlants, LAND_SIZE, LAND_TILE_SIZE},
+    land::{LandPlants, LAND_SIZE},
     player::PlayerCommon,
+    projectile::HitLayer,
+    status::StatusEffects,
     GridPos, PlayerTextureResources,
 };
-use bevy::log::info;
 use bevy::prelude::*;
-use vleue_kinetoscope::{
-    AnimatedImage, AnimatedImageController, AnimatedImagePlugin, AnimationPlayed,
-};
+use std::f32::consts::PI;
+use vleue_kinetoscope::{AnimatedImage, AnimatedImageController};
+
+/// How long a vault over a plant takes, in seconds
+const VAULT_SECONDS: f32 = 1.;
+/// Pixels above the ground at the top of the vault
+const VAULT_HEIGHT: f32 = 40.;
+/// Walking speed once the pole is lost, compared to running with it
+const WALK_SPEED_AFTER_VAULT: f32 = 0.45;
+
+/// What a [JumpingZombie] is doing with its pole
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultState {
+    /// Running with the pole, it vaults over the first plant it meets
+    Running,
+    /// In the air, see [Vaulting]
+    Vaulting,
+    /// The pole is lost, it walks and eats like any other zombie
+    Walking,
+}
 
+/// A zombie running with a pole, vaulting over the first plant in its way
 #[derive(Component)]
-pub struct JumpingZombie;
+pub struct JumpingZombie {
+    pub state: VaultState,
+    /// Walking animation once the pole is lost
+    pub walking: Handle<AnimatedImage>,
+}
+
+/// Marker component for plants too tall to be vaulted over.
+/// A vaulting zombie running into one loses its pole.
+#[derive(Component)]
+pub struct BlocksVault;
+
+/// The arc of a vault in progress
+#[derive(Component)]
+pub struct Vaulting {
+    from_x: f32,
+    to_x: f32,
+    ground_y: f32,
+    timer: Timer,
+}
+
+impl Vaulting {
+    /// A vault from `from` to `to_x` on the same row
+    pub fn new(from: Vec2, to_x: f32, seconds: f32) -> Self {
+        Vaulting {
+            from_x: from.x,
+            to_x,
+            ground_y: from.y,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+
+    /// Where the zombie lands
+    pub fn landing(&self) -> Vec2 {
+        Vec2::new(self.to_x, self.ground_y)
+    }
+}
 
 impl JumpingZombie {
-    pub fn create(pos: GridPos, commands: &mut Commands, textures: &Res<PlayerTextureResources>) {
-        commands.spawn((
-            PlayerCommon::new("Jumping Zombie", 100.),
-            ZombieCommon::new(
-                textures.jumping_zombie.clone(),
-                textures.eating_zombie.clone(),
-            ),
-            JumpingZombie,
-            AnimatedImageController::play(textures.jumping_zombie.clone()),
-            pos.to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
-        ));
+    pub fn create(
+        pos: GridPos,
+        commands: &mut Commands,
+        textures: &PlayerTextureResources,
+    ) -> Entity {
+        commands
+            .spawn((
+                PlayerCommon::new("Jumping Zombie", 100.),
+                ZombieCommon::new(
+                    textures.jumping_zombie.clone(),
+                    textures.eating_zombie.clone(),
+                ),
+                JumpingZombie {
+                    state: VaultState::Running,
+                    walking: textures.basic_zombie.clone(),
+                },
+                ZombieStats::new(20., 4., 0.5),
+                ZombieType::Jumping,
+                AnimatedImageController::play(textures.jumping_zombie.clone()),
+                pos.to_world_transform(PLAYERS_Z + LAND_SIZE.y - pos.y + 0.5),
+            ))
+            .id()
+    }
+
+    fn lose_pole(&mut self, stats: &mut ZombieStats, common: &mut ZombieCommon) {
+        self.state = VaultState::Walking;
+        stats.speed *= WALK_SPEED_AFTER_VAULT;
+        common.walking = self.walking.clone();
     }
 }
 
-/// For jumping zombie, it can jump over the first plant it meets.
-pub fn jump_over_first_plant(
-    zombies: Query<(&mut Transform, &ZombieCommon), (With<JumpingZombie>, Changed<ZombieCommon>)>,
+/// Running zombies vault over the plant they reach, or lose
+/// their pole if it can't be vaulted over.
+///
+/// Runs before zombies move, so they never start eating
+/// a plant they could vault over. Zombies that can't move
+/// (e.g. frozen) don't start a vault either.
+pub fn start_vaults(
+    mut commands: Commands,
+    zombies: Query<
+        (
+            Entity,
+            &mut JumpingZombie,
+            &Transform,
+            &mut ZombieStats,
+            &mut ZombieCommon,
+            &mut HitLayer,
+            &StatusEffects,
+        ),
+        Without<Vaulting>,
+    >,
     land_plants: Res<LandPlants>,
+    blockers: Query<(), With<BlocksVault>>,
 ) {
-    'iter_zombies: for (mut pos, zombie) in zombies {
-        // We only do business when it's eating the first plant.
-        // So if it's not eating, we do nothing.
-        if zombie.state != ZombieState::Eating {
+    for (entity, mut zombie, transform, mut stats, mut common, mut layer, effects) in zombies {
+        if zombie.state != VaultState::Running || effects.speed() <= 0. {
+            continue;
+        }
+        let grid_pos: GridPos = (*transform).into();
+        let Some(plant) = land_plants.get(grid_pos) else {
+            continue;
+        };
+
+        if blockers.contains(*plant) {
+            zombie.lose_pole(&mut stats, &mut common);
             continue;
         }
 
-        // Now it's eating. Check if we're at the first plant.
-        let grid_pos: GridPos = (*pos).into();
-        let (zombie_x, zombie_y): (i32, i32) = grid_pos.into();
-        // Check the tiles to the right of this zombie.
-        // If all of them are empty, then we're at the first plant.
-        '_iter_land_columns: for x in (zombie_x + 1)..(LAND_SIZE.x as i32) {
-            if let Some(_plant) = land_plants.get((x, zombie_y)) {
-                // It's not empty, so we're not at the first plant.
-                // We should do nothing for this zombie and
-                // go over to the next zombie.
-                continue 'iter_zombies;
-            }
-        } // otherwise we're at the first plant
-
-        // We are at the first plant!
-        // Jump over it
-        let new_pos = GridPos::new(zombie_x - 1, zombie_y);
-        pos.translation = new_pos.to_world().extend(pos.translation.z);
+        // Land on the center of the tile behind the plant
+        let (plant_x, plant_y): (i32, i32) = grid_pos.into();
+        let to_x = GridPos::new(plant_x - 1, plant_y).to_world().x;
+        zombie.state = VaultState::Vaulting;
+        *layer = HitLayer::Air;
+        commands.entity(entity).insert(Vaulting::new(
+            transform.translation.xy(),
+            to_x,
+            VAULT_SECONDS,
+        ));
+    }
+}
+
+/// Move vaulting zombies along their arc, and land them.
+///
+/// Slowed zombies vault slower, and frozen ones hang in the air.
+pub fn vault(
+    mut commands: Commands,
+    time: Res<Time>,
+    zombies: Query<(
+        Entity,
+        &mut JumpingZombie,
+        &mut Vaulting,
+        &mut Transform,
+        &mut ZombieStats,
+        &mut ZombieCommon,
+        &mut HitLayer,
+        &StatusEffects,
+    )>,
+) {
+    for (
+        entity,
+        mut zombie,
+        mut vaulting,
+        mut transform,
+        mut stats,
+        mut common,
+        mut layer,
+        effects,
+    ) in zombies
+    {
+        vaulting.timer.tick(time.delta().mul_f32(effects.speed()));
+        let progress = vaulting.timer.fraction();
+        transform.translation.x = vaulting.from_x.lerp(vaulting.to_x, progress);
+        transform.translation.y = vaulting.ground_y + (PI * progress).sin() * VAULT_HEIGHT;
+
+        if vaulting.timer.finished() {
+            transform.translation.y = vaulting.ground_y;
+            *layer = HitLayer::Ground;
+            zombie.lose_pole(&mut stats, &mut common);
+            commands.entity(entity).remove::<Vaulting>();
+        }
     }
 }
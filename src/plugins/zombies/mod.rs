@@ -1,21 +1,34 @@
 use super::{
+    interpolation::Interpolated,
     land::{LandPlants, LAND_SIZE},
+    lawnmower::ZombieReachedHouse,
     plants::PlantCommon,
+    projectile::HitLayer,
+    status::StatusEffects,
     GridPos,
 };
-use crate::{plugins::player::PlayerCommon, GameState};
+use crate::{
+    plugins::player::{DamageKind, Damaged, PlayerCommon},
+    GameState, PauseState,
+};
+use armor::drop_broken_armor;
 use bevy::log::{debug, info};
 use bevy::{
     ecs::{component::HookContext, entity::EntityEquivalent, world::DeferredWorld},
     prelude::*,
 };
-use jumping_zombie::jump_over_first_plant;
+use flag_zombie::FlagZombie;
+use jumping_zombie::{start_vaults, vault, Vaulting};
+use registry::ZombieRegistry;
 use vleue_kinetoscope::{AnimatedImage, AnimatedImageController};
 
+pub mod armor;
 pub mod basic_zombie;
 pub mod conehead_zombie;
 pub mod create_zombie;
+pub mod flag_zombie;
 pub mod jumping_zombie;
+pub mod registry;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum ZombieState {
@@ -24,7 +37,7 @@ pub enum ZombieState {
 }
 
 #[derive(Component)]
-#[require(PlayerCommon, Transform)]
+#[require(PlayerCommon, Transform, Interpolated, HitLayer)]
 #[component(on_add=zombie_on_add, on_remove=zombie_on_remove)]
 pub struct ZombieCommon {
     pub walking: Handle<AnimatedImage>,
@@ -58,37 +71,77 @@ fn zombie_on_remove<'w>(mut world: DeferredWorld<'w>, context: HookContext) {
     land_zombie.remove_zombie(context.entity, grid_pos.round().y as usize);
 }
 
+/// How much faster zombies walk and eat than their stats say
 #[cfg(feature = "debug_mode")]
-const ZOMBIE_SPEED: f32 = 1. / 10.;
+const ZOMBIE_SPEEDUP: f32 = 10.;
 #[cfg(not(feature = "debug_mode"))]
-const ZOMBIE_SPEED: f32 = 1. / 100.;
+const ZOMBIE_SPEEDUP: f32 = 1.;
+
+/// How fast a kind of zombie walks and eats
+#[derive(Component, Debug, Clone)]
+pub struct ZombieStats {
+    /// Pixels walked per second
+    pub speed: f32,
+    /// Damage done to the plant being eaten by each bite
+    pub bite_damage: f32,
+    /// Time between two bites
+    pub bite_timer: Timer,
+}
+
+impl ZombieStats {
+    pub fn new(speed: f32, bite_damage: f32, bite_interval: f32) -> Self {
+        ZombieStats {
+            speed: speed * ZOMBIE_SPEEDUP,
+            bite_damage,
+            bite_timer: Timer::from_seconds(bite_interval / ZOMBIE_SPEEDUP, TimerMode::Repeating),
+        }
+    }
+}
 
 /// Move zombies forward
 fn move_zombies(
     mut commands: Commands,
     time: Res<Time>,
-    zombies: Query<(&mut Transform, &mut ZombieCommon)>,
+    zombies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut ZombieCommon,
+            &mut ZombieStats,
+            &StatusEffects,
+        ),
+        Without<Vaulting>,
+    >,
     mut health: Query<&mut PlayerCommon, With<PlantCommon>>,
     land_plants: Res<LandPlants>,
-    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (mut position, mut common) in zombies {
+    for (entity, mut position, mut common, mut stats, effects) in zombies {
         let grid_pos: GridPos = (*position).into();
         let new_state = if let Some(target) = land_plants.get(grid_pos) {
-            // We've got a plant here, eat it
-            let mut player = health.get_mut(*target).unwrap();
-            player.damage(
-                &mut commands,
-                time.delta().as_millis() as f32 * ZOMBIE_SPEED,
-            );
+            // We've got a plant here, bite it
+            stats
+                .bite_timer
+                .tick(time.delta().mul_f32(effects.eat_rate()));
+            if stats.bite_timer.just_finished() {
+                let mut player = health.get_mut(*target).unwrap();
+                player.damage(
+                    &mut commands,
+                    *target,
+                    Damaged::new(stats.bite_damage, DamageKind::Bite, entity),
+                );
+            }
             ZombieState::Eating
         } else {
             // No plant here, move forward
-            position.translation.x -= time.delta().as_millis() as f32 * ZOMBIE_SPEED;
+            position.translation.x -= time.delta_secs() * stats.speed * effects.speed();
+            // Start over with the next plant
+            stats.bite_timer.reset();
 
             let grid_pos: GridPos = (*position).into();
             if grid_pos.x < -0.5 {
-                next_state.set(GameState::End { win: false });
+                commands.trigger(ZombieReachedHouse {
+                    row: grid_pos.round().y as usize,
+                });
             }
 
             ZombieState::Walking
@@ -103,10 +156,23 @@ fn move_zombies(
 /// Update zombies' animation based on state (eating or walking)
 fn update_zombie_animation(
     mut commands: Commands,
-    changed_zombies: Query<(Entity, &ZombieCommon), Changed<ZombieCommon>>,
+    changed_zombies: Query<
+        (
+            Entity,
+            &ZombieCommon,
+            Option<&FlagZombie>,
+            Option<&mut Sprite>,
+        ),
+        Changed<ZombieCommon>,
+    >,
 ) {
-    for (entity, common) in changed_zombies {
+    for (entity, common, flag, sprite) in changed_zombies {
         commands.entity(entity).remove::<AnimatedImageController>();
+        if let (ZombieState::Walking, Some(flag), Some(mut sprite)) = (&common.state, flag, sprite)
+        {
+            sprite.image = flag.walking.clone();
+            continue;
+        }
         commands
             .entity(entity)
             .insert(AnimatedImageController::play(match common.state {
@@ -120,17 +186,23 @@ pub struct ZombiePlugin;
 
 impl Plugin for ZombiePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, setup_landzombies);
+        app.add_systems(PreStartup, (setup_landzombies, ZombieRegistry::setup));
         app.add_systems(OnEnter(GameState::Running), create_zombie::setup);
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
-                create_zombie::create_zombie_randomly,
+                create_zombie::send_waves,
+                start_vaults.before(move_zombies),
+                vault,
                 move_zombies,
-                update_zombie_animation,
-                jump_over_first_plant,
+                drop_broken_armor,
             )
-                .run_if(in_state(GameState::Running)),
+                .run_if(in_state(PauseState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            (create_zombie::remove_banner, update_zombie_animation)
+                .run_if(in_state(PauseState::Playing)),
         );
     }
 }
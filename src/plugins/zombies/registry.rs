@@ -0,0 +1,175 @@
+//! Every zombie of the game and when it may be sent, keyed by [ZombieType].
+//!
+//! Waves are built by sampling this table, so adding a zombie is
+//! adding a [ZombieType], its `create` fn and an entry here.
+
+use super::{
+    basic_zombie::BasicZombie, conehead_zombie::ConeheadZombie, create_zombie::ZombieType,
+    flag_zombie::FlagZombie, jumping_zombie::JumpingZombie,
+};
+use crate::plugins::{GridPos, PlayerTextureResources};
+use bevy::{platform::collections::HashMap, prelude::*};
+use fastrand::Rng;
+
+pub struct ZombieDefinition {
+    /// How likely it's picked compared to the others, 0 for never
+    pub weight: u32,
+    /// How many points of the wave budget it uses
+    pub cost: u32,
+    /// The first wave it may be sent in, starting from 1
+    pub first_wave: u32,
+    /// Rows it may be sent on, `None` for any row
+    pub rows: Option<&'static [u32]>,
+    /// Sent in front of every huge wave, on top of the budget
+    pub leads_huge_waves: bool,
+    pub create: fn(GridPos, &mut Commands, &PlayerTextureResources) -> Entity,
+}
+
+impl ZombieDefinition {
+    /// The rows it may be sent on, out of the `rows` of the level
+    fn allowed_rows(&self, rows: u32) -> Vec<u32> {
+        match self.rows {
+            None => (0..rows).collect(),
+            Some(allowed) => allowed.iter().copied().filter(|row| *row < rows).collect(),
+        }
+    }
+}
+
+/// The **Resource** holding the definition of every zombie
+#[derive(Resource)]
+pub struct ZombieRegistry(HashMap<ZombieType, ZombieDefinition>);
+
+impl Default for ZombieRegistry {
+    fn default() -> Self {
+        let mut zombies = HashMap::default();
+        zombies.insert(
+            ZombieType::Basic,
+            ZombieDefinition {
+                weight: 4,
+                cost: 1,
+                first_wave: 1,
+                rows: None,
+                leads_huge_waves: false,
+                create: BasicZombie::create,
+            },
+        );
+        zombies.insert(
+            ZombieType::Conehead,
+            ZombieDefinition {
+                weight: 3,
+                cost: 2,
+                first_wave: 3,
+                rows: None,
+                leads_huge_waves: false,
+                create: ConeheadZombie::create,
+            },
+        );
+        zombies.insert(
+            ZombieType::Jumping,
+            ZombieDefinition {
+                weight: 2,
+                cost: 2,
+                first_wave: 5,
+                rows: None,
+                leads_huge_waves: false,
+                create: JumpingZombie::create,
+            },
+        );
+        zombies.insert(
+            ZombieType::Flag,
+            ZombieDefinition {
+                weight: 0,
+                cost: 1,
+                first_wave: 1,
+                rows: None,
+                leads_huge_waves: true,
+                create: FlagZombie::create,
+            },
+        );
+        ZombieRegistry(zombies)
+    }
+}
+
+impl ZombieRegistry {
+    pub fn setup(mut commands: Commands) {
+        commands.insert_resource(ZombieRegistry::default());
+    }
+
+    pub fn get(&self, kind: ZombieType) -> &ZombieDefinition {
+        self.0
+            .get(&kind)
+            .expect("Every zombie type has a definition")
+    }
+
+    pub fn spawn(
+        &self,
+        kind: ZombieType,
+        pos: GridPos,
+        commands: &mut Commands,
+        textures: &PlayerTextureResources,
+    ) -> Entity {
+        (self.get(kind).create)(pos, commands, textures)
+    }
+
+    /// The zombie sent in front of huge waves, if the level
+    /// `allowed` one. The first in the level's list wins.
+    pub fn leader(&self, allowed: &[ZombieType]) -> Option<ZombieType> {
+        allowed
+            .iter()
+            .copied()
+            .find(|kind| self.get(*kind).leads_huge_waves)
+    }
+
+    /// Pick a zombie and its row for a wave, weighted by spawn weight.
+    ///
+    /// Only zombies from `allowed` are picked, if they are unlocked
+    /// by `wave`, fit in the `budget` and may walk on one of the
+    /// `rows` of the level. `None` means nothing fits the budget.
+    pub fn sample(
+        &self,
+        allowed: &[ZombieType],
+        wave: u32,
+        budget: u32,
+        rows: u32,
+        rng: &mut Rng,
+    ) -> Option<(ZombieType, u32)> {
+        // Go through the level's list rather than the map,
+        // so that a seed always picks the same zombies
+        let candidates: Vec<(ZombieType, &ZombieDefinition)> = allowed
+            .iter()
+            .map(|kind| (*kind, self.get(*kind)))
+            .filter(|(_, definition)| {
+                definition.weight > 0
+                    && definition.first_wave <= wave
+                    && definition.cost <= budget
+                    && !definition.allowed_rows(rows).is_empty()
+            })
+            .collect();
+        let total: u32 = candidates
+            .iter()
+            .map(|(_, definition)| definition.weight)
+            .sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.u32(0..total);
+        let (kind, definition) = candidates
+            .into_iter()
+            .find(|(_, definition)| {
+                if roll < definition.weight {
+                    return true;
+                }
+                roll -= definition.weight;
+                false
+            })
+            .expect("The roll is below the total weight");
+        let row = match definition.rows {
+            None => rng.u32(0..rows),
+            Some(_) => rng
+                .choice(definition.allowed_rows(rows))
+                .expect("Candidates have an allowed row"),
+        };
+        Some((kind, row))
+    }
+}